@@ -20,90 +20,609 @@
 
 /// The settings and business logic of the resulting binary executable.
 pub struct Application {
-    /// The processes to be called.
-    applications: Vec<String>,
+    /// The fingerprint cache to skip unchanged steps with.
+    cache: Option<crate::Cache>,
 
-    /// The command line arguments of the processes to be called.
-    arguments: Vec<Vec<String>>,
+    /// The history to append the results of each run to.
+    history: Option<crate::History>,
 
-    /// The error messages to show in case the processes should fail.
-    error_messages: Vec<Option<String>>,
+    /// The information about the workspace to pass to the steps, if known.
+    metadata: Option<std::sync::Arc<crate::Metadata>>,
 
-    /// The exit code to return by `main`.
-    exit_codes: Vec<sysexits::ExitCode>,
+    /// The optimisation steps to be run.
+    steps: Vec<std::sync::Arc<dyn crate::Step>>,
 
-    /// The verbosities of the respective processes.
-    verbosities: Vec<crate::Verbosity>,
+    /// The Markdown summary to write after each run.
+    summary: Option<crate::Summary>,
+
+    /// The verbosity of the summary.
+    verbosity: crate::Verbosity,
 }
 
 impl Application {
+    /// Skip steps whose inputs did not change since their last success.
+    #[must_use]
+    pub(crate) fn cache(mut self, cache: crate::Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Append the results of each run to the given history.
+    #[must_use]
+    pub(crate) fn history(mut self, history: crate::History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// List the names of the configured steps together with their command
+    /// lines.
+    ///
+    /// The names are the ones to select the steps with, see `select`.
+    #[must_use]
+    pub fn list(&self) -> String {
+        let width = self
+            .steps
+            .iter()
+            .map(|step| step.name().len())
+            .max()
+            .unwrap_or_default();
+
+        self.steps
+            .iter()
+            .map(|step| format!("{:width$}  {step}", step.name()))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Run the Cargo subcommand `cargo optimise` with the given command line
+    /// arguments.
+    ///
+    /// The steps are composed from the configuration of the current project
+    /// and run as requested by the arguments.  The returned exit code is the
+    /// one to return from the `main` function of the binary executable.
+    #[must_use]
+    pub fn main(arguments: Vec<String>) -> sysexits::ExitCode {
+        crate::pipeline::run(arguments)
+    }
+
+    /// Pass the given information about the workspace to the steps.
+    ///
+    /// The workspace root is passed on to the steps by their `crate::Context`.
+    #[must_use]
+    pub(crate) fn metadata(mut self, metadata: std::sync::Arc<crate::Metadata>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
     /// Create a new application instance to run over the current project.
+    ///
+    /// By default, no fingerprint cache is used such that all steps are run.
+    /// Neither are the results recorded in a history nor in a summary.
     #[must_use]
-    pub fn new(
-        applications: Vec<String>,
-        arguments: Vec<Vec<String>>,
-        error_messages: Vec<Option<String>>,
-        exit_codes: Vec<sysexits::ExitCode>,
-        verbosities: Vec<crate::Verbosity>,
+    pub const fn new(
+        steps: Vec<std::sync::Arc<dyn crate::Step>>,
+        verbosity: crate::Verbosity,
     ) -> Self {
         Self {
-            applications,
-            arguments,
-            error_messages,
-            exit_codes,
-            verbosities,
+            cache: None,
+            history: None,
+            metadata: None,
+            steps,
+            summary: None,
+            verbosity,
         }
     }
 
-    /// Run the configured instance as binary executable.
+    /// Derive an application consisting of the non-mutating steps only.
+    ///
+    /// Steps which modify the sources of the project, such as `cargo fmt`, are
+    /// removed from the copy.  The remaining steps keep their order.  The copy
+    /// does neither use a fingerprint cache nor a history nor a summary.  The
+    /// information about the workspace is passed on.
+    #[must_use]
+    pub fn non_mutating(&self) -> Self {
+        Self {
+            metadata: self.metadata.clone(),
+            ..Self::new(
+                self.steps
+                    .iter()
+                    .filter(|step| !step.is_mutating())
+                    .cloned()
+                    .collect(),
+                self.verbosity,
+            )
+        }
+    }
+
+    /// The indices of the steps each step has to wait for.
     ///
-    /// First of all, it will be checked whether the specified processes where
-    /// defined uniformly.  This is, that for each process, there is a set of
-    /// command line arguments, an error message -- just to be sure -- as well
-    /// as an exit code from `sysexits` to be returned in case the error the
-    /// message is configurated for should occur.  This sanity check is not only
-    /// used for debugging but also ensures -- in case that this crate should be
-    /// bound as a dependency -- that all the configured optimisation steps will
-    /// have sufficient data to originate from.
+    /// A step waits for the steps it needs, or for the preceding step if it
+    /// does not declare its needs.  Needed steps which are not configured are
+    /// ignored.  Mutating steps wait for all preceding steps and all following
+    /// steps wait for them such that they never run in parallel to others.
+    fn dependencies(&self) -> Vec<Vec<usize>> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let mut dependencies = if step.is_mutating() {
+                    (0..index).collect()
+                } else if let Some(needs) = step.requirements() {
+                    self.steps
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, other)| needs.iter().any(|name| name == other.name()))
+                        .map(|(other, _)| other)
+                        .collect()
+                } else {
+                    index.checked_sub(1).into_iter().collect::<Vec<usize>>()
+                };
+
+                for (other, preceding) in self.steps[..index].iter().enumerate() {
+                    if preceding.is_mutating() && !dependencies.contains(&other) {
+                        dependencies.push(other);
+                    }
+                }
+
+                dependencies
+            })
+            .collect()
+    }
+
+    /// Run the configured steps and report their outcomes.
     ///
-    /// Then, the actual processes will be spawned and run using the settings of
-    /// this instance.  The processes will be created in their order of
-    /// appearance in `self.applications` such that the index position of the
-    /// application to be called also returns the corresponding data from the
-    /// other vectors held.  Every process is required to finish before the next
-    /// one will be spawned.
+    /// Each step is spawned as soon as the steps it has to wait for succeeded,
+    /// see `crate::Step::requirements`.  Independent steps are run in
+    /// parallel.  By default, each step waits for the preceding one.
     ///
-    /// In case one process should not succeed, the corresponding error message
-    /// will be written to `stderr` and this application will be aborted with
-    /// the configured `sysexits::ExitCode`.  If each process succeeds, the
-    /// return value is `sysexits::ExitCode::Ok`.
+    /// If a fingerprint cache is configured, steps which succeeded before with
+    /// identical inputs are reported as cached and skipped.  The files of the
+    /// workspace are read once per run and once after each mutating step.  The
+    /// fingerprints of mutating steps are taken after they were run such that
+    /// they match the state they leave the project in.
+    ///
+    /// In case one step should not succeed, the corresponding error message
+    /// will be written to `stderr` and the steps depending on it will be
+    /// skipped.  The exit code of the returned report is the configured
+    /// `sysexits::ExitCode` of the first failed step in the order of
+    /// `self.steps` then.  If each step succeeds, it is
+    /// `sysexits::ExitCode::Ok`.  Steps whose needs form a cycle are skipped
+    /// and let the run fail with `sysexits::ExitCode::Config`.
     #[must_use]
-    pub fn run(&self) -> sysexits::ExitCode {
-        if self.applications.len() != self.arguments.len()
-            || self.applications.len() != self.error_messages.len()
-            || self.applications.len() != self.exit_codes.len()
+    pub(crate) fn execute(&self) -> crate::Report {
+        if let Some(cache) = &self.cache {
+            cache.refresh();
+        }
+
+        let context = crate::Context::new(
+            self.verbosity,
+            self.metadata
+                .as_ref()
+                .map(|metadata| metadata.workspace().root().to_path_buf()),
+        );
+        let dependencies = self.dependencies();
+        let lock = std::sync::Mutex::new(());
+        let mut outcomes = vec![None::<crate::StepOutcome>; self.steps.len()];
+        let mut blocked = vec![false; self.steps.len()];
+        let mut started = vec![false; self.steps.len()];
+
+        std::thread::scope(|scope| {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let mut running = 0;
+
+            loop {
+                for (index, step) in self.steps.iter().enumerate() {
+                    if started[index]
+                        || dependencies[index]
+                            .iter()
+                            .any(|&other| outcomes[other].is_none() && !blocked[other])
+                    {
+                        continue;
+                    }
+
+                    started[index] = true;
+
+                    if dependencies[index].iter().any(|&other| blocked[other]) {
+                        blocked[index] = true;
+                        outcomes[index] =
+                            Some(crate::StepOutcome::new(step.name(), crate::Status::Skipped));
+                        continue;
+                    }
+
+                    let sender = sender.clone();
+                    let (context, lock) = (&context, &lock);
+                    running += 1;
+                    scope.spawn(move || {
+                        // The receiver outlives all senders.
+                        let _ = sender.send((index, self.run_step(step.as_ref(), context, lock)));
+                    });
+                }
+
+                if running == 0 {
+                    break;
+                }
+
+                if let Ok((index, outcome)) = receiver.recv() {
+                    running -= 1;
+                    blocked[index] = matches!(outcome.status(), crate::Status::Failed(_));
+                    outcomes[index] = Some(outcome);
+                }
+            }
+        });
+
+        let cycle = outcomes.iter().any(Option::is_none);
+        let outcomes = outcomes
+            .into_iter()
+            .zip(&self.steps)
+            .map(|(outcome, step)| {
+                outcome
+                    .unwrap_or_else(|| crate::StepOutcome::new(step.name(), crate::Status::Skipped))
+            })
+            .collect::<Vec<crate::StepOutcome>>();
+
+        let code = if cycle {
+            eprintln!("The needs of the steps form a cycle!");
+            sysexits::ExitCode::Config
+        } else {
+            outcomes
+                .iter()
+                .find_map(|outcome| match outcome.status() {
+                    crate::Status::Failed(code) => Some(code),
+                    _ => None,
+                })
+                .unwrap_or(sysexits::ExitCode::Ok)
+        };
+
+        crate::Report::new(code, outcomes)
+    }
+
+    /// Restrict the configured steps to the given selection.
+    ///
+    /// If any step names are given to run only, all other steps are removed.
+    /// Afterwards, the steps named to skip are removed.  The remaining steps
+    /// keep their order.
+    #[must_use]
+    pub fn select(mut self, only: &[String], skip: &[String]) -> Self {
+        self.steps.retain(|step| {
+            let name = step.name().to_string();
+            (only.is_empty() || only.contains(&name)) && !skip.contains(&name)
+        });
+        self
+    }
+
+    /// The names of the configured steps in their order of appearance.
+    #[must_use]
+    pub fn steps(&self) -> Vec<&str> {
+        self.steps.iter().map(|step| step.name()).collect()
+    }
+
+    /// Run the given step unless the cache shows it to be unchanged.
+    ///
+    /// The given lock serialises the updates of the cache among the steps run
+    /// in parallel.  Since mutating steps are not run in parallel to others,
+    /// the cache can be refreshed after them.
+    fn run_step(
+        &self,
+        step: &dyn crate::Step,
+        context: &crate::Context,
+        lock: &std::sync::Mutex<()>,
+    ) -> crate::StepOutcome {
+        if step.is_skipped() {
+            return step.run(context);
+        }
+
+        let fingerprint = self.cache.as_ref().map(|cache| cache.fingerprint(step));
+
+        if let (Some(cache), Some(fingerprint)) = (&self.cache, &fingerprint) {
+            if cache.contains(step, fingerprint) {
+                if self.verbosity > crate::Verbosity::Silent {
+                    println!("{step} (cached)");
+                }
+
+                return crate::StepOutcome::new(step.name(), crate::Status::Cached);
+            }
+        }
+
+        let outcome = step.run(context);
+
+        if let (true, Some(cache)) = (step.is_mutating(), &self.cache) {
+            cache.refresh();
+        }
+
+        if let (crate::Status::Passed, Some(cache), Some(fingerprint)) =
+            (outcome.status(), &self.cache, fingerprint)
         {
-            eprintln!(
-                "Internal error:  \
-                non-uniform count of process specification details!"
-            );
-            return sysexits::ExitCode::Software;
-        }
-
-        for i in 0..self.applications.len() {
-            match crate::Process::new(
-                &self.applications[i],
-                self.arguments[i].clone(),
-                self.verbosities[i],
-            )
-            .handle(self.error_messages[i].as_deref(), self.exit_codes[i])
-            {
-                None => (),
-                Some(code) => return code,
+            let fingerprint = if step.is_mutating() {
+                cache.fingerprint(step)
+            } else {
+                fingerprint
+            };
+            let _guard = lock.lock();
+
+            if cache.store(step, &fingerprint).is_err() {
+                eprintln!("Failed to update the cache for '{step}'!");
             }
         }
 
-        sysexits::ExitCode::Ok
+        outcome
+    }
+
+    /// Write a Markdown summary after each run.
+    #[must_use]
+    pub(crate) fn summary(mut self, summary: crate::Summary) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    /// Derive an application running the Cargo processes with the given
+    /// toolchain.
+    ///
+    /// The copy consists of the non-mutating steps only, see `non_mutating`,
+    /// such that the sources are not modified once per toolchain.  Steps which
+    /// do not depend on the toolchain are left out, as well, since they would
+    /// report the same outcome for each toolchain, see `Step::toolchain`.  The
+    /// build artifacts are written to the given directory.  A configured
+    /// fingerprint cache is keyed by the given toolchain, as well, while the
+    /// history and the Markdown summary label the runs with it.
+    #[must_use]
+    pub fn toolchain(&self, toolchain: &str, target: &std::path::Path) -> Self {
+        let mut application = self.non_mutating();
+        application.cache = self.cache.as_ref().map(|cache| cache.toolchain(toolchain));
+        application.history = self
+            .history
+            .as_ref()
+            .map(|history| history.toolchain(toolchain));
+        application.summary = self
+            .summary
+            .as_ref()
+            .map(|summary| summary.toolchain(toolchain));
+        application.steps = application
+            .steps
+            .iter()
+            .filter_map(|step| step.toolchain(toolchain, target))
+            .collect();
+        application
+    }
+
+    /// Run the steps and record the report.
+    ///
+    /// The steps will be run by `execute`.  If a history is configured, the
+    /// results will be appended to it.  The same applies to the Markdown
+    /// summary, for which a snapshot of the sources will be taken before the
+    /// steps are run.
+    pub(crate) fn record(&self) -> crate::Report {
+        let snapshot = self.summary.as_ref().map(crate::Summary::snapshot);
+        let report = self.execute();
+
+        if let (Some(summary), Some(snapshot)) = (&self.summary, &snapshot) {
+            if summary.write(&report, snapshot).is_err() {
+                eprintln!("Failed to write the Markdown summary!");
+            }
+        }
+
+        if let Some(history) = &self.history {
+            if history.append(&report).is_err() {
+                eprintln!("Failed to append the results to the history!");
+            }
+        }
+
+        report
+    }
+
+    /// Run the configured instance as binary executable.
+    ///
+    /// The steps will be run and recorded by `record`.  Afterwards, a summary
+    /// table naming the status and the duration of each step will be written
+    /// to `stdout` unless the verbosity is set to `Verbosity::Silent`.
+    #[must_use]
+    pub fn run(&self) -> sysexits::ExitCode {
+        let report = self.record();
+
+        if self.verbosity > crate::Verbosity::Silent {
+            println!("\n{report}");
+        }
+
+        report.code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// The names of the steps in the order they were run.
+    type Log = std::sync::Arc<std::sync::Mutex<Vec<String>>>;
+
+    /// A step recording that it was run.
+    struct Probe {
+        /// Whether the step fails.
+        failing: bool,
+
+        /// The log to record the run in.
+        log: Log,
+
+        /// Whether the step is mutating.
+        mutating: bool,
+
+        /// The name of the step.
+        name: String,
+
+        /// The steps the step needs, if declared.
+        needs: Option<Vec<String>>,
+    }
+
+    impl Probe {
+        /// Let the step fail.
+        fn failing(mut self) -> Self {
+            self.failing = true;
+            self
+        }
+
+        /// Let the step be mutating.
+        fn mutating(mut self) -> Self {
+            self.mutating = true;
+            self
+        }
+
+        /// Declare the steps the step needs.
+        fn needs(mut self, steps: &[&str]) -> Self {
+            self.needs = Some(steps.iter().map(ToString::to_string).collect());
+            self
+        }
+
+        /// Create a succeeding step recording its runs in the given log.
+        fn new(name: &str, log: &Log) -> Self {
+            Self {
+                failing: false,
+                log: std::sync::Arc::clone(log),
+                mutating: false,
+                name: name.into(),
+                needs: None,
+            }
+        }
+    }
+
+    impl std::fmt::Display for Probe {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "probe {}", self.name)
+        }
+    }
+
+    impl crate::Step for Probe {
+        fn is_mutating(&self) -> bool {
+            self.mutating
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn requirements(&self) -> Option<&[String]> {
+            self.needs.as_deref()
+        }
+
+        fn run(&self, _: &crate::Context) -> crate::StepOutcome {
+            self.log.lock().unwrap().push(self.name.clone());
+
+            crate::StepOutcome::new(
+                &self.name,
+                if self.failing {
+                    crate::Status::Failed(sysexits::ExitCode::DataErr)
+                } else {
+                    crate::Status::Passed
+                },
+            )
+        }
+    }
+
+    /// Create an application running the given steps.
+    fn application(steps: Vec<Probe>) -> super::Application {
+        super::Application::new(
+            steps
+                .into_iter()
+                .map(|step| std::sync::Arc::new(step) as std::sync::Arc<dyn crate::Step>)
+                .collect(),
+            crate::Verbosity::Silent,
+        )
+    }
+
+    /// The names of the steps of the given report with the given status.
+    fn named(report: &crate::Report, status: fn(crate::Status) -> bool) -> Vec<&str> {
+        report
+            .outcomes()
+            .iter()
+            .filter(|outcome| status(outcome.status()))
+            .map(crate::StepOutcome::name)
+            .collect()
+    }
+
+    #[test]
+    fn chain_by_default() {
+        let log = Log::default();
+        let application = application(vec![
+            Probe::new("a", &log),
+            Probe::new("b", &log),
+            Probe::new("c", &log),
+        ]);
+
+        assert_eq!(application.dependencies(), [vec![], vec![0], vec![1]]);
+        assert!(application.execute().code().is_success());
+        assert_eq!(*log.lock().unwrap(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn cycle() {
+        let log = Log::default();
+        let report = application(vec![
+            Probe::new("a", &log).needs(&["b"]),
+            Probe::new("b", &log).needs(&["a"]),
+            Probe::new("c", &log).needs(&[]),
+        ])
+        .execute();
+
+        assert!(matches!(report.code(), sysexits::ExitCode::Config));
+        assert_eq!(
+            named(&report, |status| matches!(status, crate::Status::Skipped)),
+            ["a", "b"]
+        );
+        assert_eq!(*log.lock().unwrap(), ["c"]);
+    }
+
+    #[test]
+    fn explicit_needs() {
+        let log = Log::default();
+        let application = application(vec![
+            Probe::new("a", &log),
+            Probe::new("b", &log),
+            Probe::new("c", &log).needs(&["a"]),
+            Probe::new("d", &log).needs(&[]),
+            Probe::new("e", &log).needs(&["a", "unconfigured"]),
+        ]);
+
+        assert_eq!(
+            application.dependencies(),
+            [vec![], vec![0], vec![0], vec![], vec![0]]
+        );
+    }
+
+    #[test]
+    fn serialise_mutating() {
+        let log = Log::default();
+        let application = application(vec![
+            Probe::new("a", &log).needs(&[]),
+            Probe::new("b", &log).needs(&[]),
+            Probe::new("fmt", &log).mutating().needs(&[]),
+            Probe::new("c", &log).needs(&[]),
+            Probe::new("d", &log).needs(&["a"]),
+        ]);
+
+        assert_eq!(
+            application.dependencies(),
+            [vec![], vec![], vec![0, 1], vec![2], vec![0, 2]]
+        );
+        assert!(application.execute().code().is_success());
+        assert_eq!(log.lock().unwrap()[2], "fmt");
+    }
+
+    #[test]
+    fn skip_after_failure() {
+        let log = Log::default();
+        let report = application(vec![
+            Probe::new("a", &log).failing(),
+            Probe::new("b", &log),
+            Probe::new("c", &log).needs(&["b"]),
+            Probe::new("d", &log).needs(&[]),
+        ])
+        .execute();
+
+        assert!(matches!(report.code(), sysexits::ExitCode::DataErr));
+        assert_eq!(
+            named(&report, |status| matches!(status, crate::Status::Skipped)),
+            ["b", "c"]
+        );
+        assert_eq!(
+            named(&report, |status| matches!(status, crate::Status::Passed)),
+            ["d"]
+        );
+        assert!(!log.lock().unwrap().contains(&"b".to_string()));
     }
 }
 
@@ -0,0 +1,511 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// An audit of the dependencies against a local `RustSec` advisory database.
+///
+/// The database is expected to be a checkout of the `RustSec` `advisory-db`
+/// repository such that no network access is required.  The audited packages
+/// are the ones Cargo resolved for the workspace, this is, the contents of
+/// `Cargo.lock`.  Only if requested, the sparse index of the registry is
+/// queried for yanked releases.
+#[derive(Clone)]
+pub struct Audit {
+    /// The directory containing the advisory database.
+    database: std::path::PathBuf,
+
+    /// The kinds of findings letting the audit fail.
+    deny: Vec<String>,
+
+    /// The identifiers of the advisories to ignore.
+    ignore: Vec<String>,
+
+    /// The information about the workspace to audit.
+    metadata: std::sync::Arc<crate::Metadata>,
+
+    /// Whether to query the sparse registry index for yanked releases.
+    yanked: bool,
+}
+
+/// A single advisory of the database.
+#[derive(serde::Deserialize)]
+struct Advisory {
+    /// The general information about the advisory.
+    advisory: AdvisoryInfo,
+
+    /// The versions of the package which are not affected.
+    #[serde(default)]
+    versions: Versions,
+}
+
+/// The general information about an advisory.
+#[derive(serde::Deserialize)]
+struct AdvisoryInfo {
+    /// The identifier of the advisory, such as `RUSTSEC-2020-0071`.
+    id: String,
+
+    /// The kind of an informational advisory, such as `unmaintained`.
+    informational: Option<String>,
+
+    /// The name of the affected package.
+    package: String,
+
+    /// The title of the advisory, if given in the front matter.
+    title: Option<String>,
+
+    /// The date the advisory was withdrawn at, if so.
+    withdrawn: Option<toml::Value>,
+}
+
+/// The versions of a package which are not affected by an advisory.
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct Versions {
+    /// The version requirements of the patched releases.
+    patched: Vec<String>,
+
+    /// The version requirements of the releases which were never affected.
+    unaffected: Vec<String>,
+}
+
+/// A release as listed in the sparse index of a registry.
+#[derive(serde::Deserialize)]
+struct IndexEntry {
+    /// The version of the release.
+    vers: String,
+
+    /// Whether the release was yanked.
+    #[serde(default)]
+    yanked: bool,
+}
+
+impl Advisory {
+    /// Whether the given version of the package is affected.
+    fn affects(&self, version: &semver::Version) -> bool {
+        !self
+            .versions
+            .patched
+            .iter()
+            .chain(&self.versions.unaffected)
+            .filter_map(|requirement| semver::VersionReq::parse(requirement).ok())
+            .any(|requirement| requirement.matches(version))
+    }
+
+    /// The kind of this advisory.
+    fn kind(&self) -> &str {
+        self.advisory
+            .informational
+            .as_deref()
+            .unwrap_or("vulnerability")
+    }
+
+    /// Read an advisory from the given contents of a file.
+    ///
+    /// Advisories are either stored as Markdown files with a TOML front matter
+    /// or, in older databases, as plain TOML files.
+    fn parse(contents: &str, markdown: bool) -> Option<Self> {
+        if !markdown {
+            return toml::from_str(contents).ok();
+        }
+
+        let (front_matter, body) = contents.strip_prefix("```toml")?.split_once("\n```")?;
+        let mut advisory = toml::from_str::<Self>(front_matter).ok()?;
+
+        if advisory.advisory.title.is_none() {
+            advisory.advisory.title = body
+                .lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|title| title.trim().into());
+        }
+
+        Some(advisory)
+    }
+
+    /// Read an advisory from the given file, see `parse`.
+    fn read(file: &std::path::Path) -> Option<Self> {
+        Self::parse(
+            &std::fs::read_to_string(file).ok()?,
+            file.extension().and_then(std::ffi::OsStr::to_str) == Some("md"),
+        )
+    }
+}
+
+impl Audit {
+    /// The files of the advisories within the database.
+    fn advisories(&self) -> Vec<std::path::PathBuf> {
+        let mut files = std::fs::read_dir(self.database.join("crates"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .flat_map(|directory| std::fs::read_dir(directory.path()).into_iter().flatten())
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(std::ffi::OsStr::to_str),
+                    Some("md" | "toml")
+                )
+            })
+            .collect::<Vec<std::path::PathBuf>>();
+
+        files.sort();
+        files
+    }
+
+    /// The files outside of the workspace the outcome of the audit depends on.
+    #[must_use]
+    pub fn inputs(&self) -> Vec<std::path::PathBuf> {
+        self.advisories()
+    }
+
+    /// Configure a new audit against the given database.
+    #[must_use]
+    pub fn new(
+        database: &std::path::Path,
+        config: &crate::AuditConfig,
+        metadata: std::sync::Arc<crate::Metadata>,
+    ) -> Self {
+        Self {
+            database: database.into(),
+            deny: config.deny().to_vec(),
+            ignore: config.ignore().to_vec(),
+            metadata,
+            yanked: config.yanked(),
+        }
+    }
+
+    /// Record the given finding depending on whether its kind is denied.
+    fn record(&self, findings: &mut crate::Findings, kind: &str, finding: String) {
+        if self.deny.iter().any(|denied| denied == kind) {
+            findings.error(finding);
+        } else {
+            findings.warning(finding);
+        }
+    }
+
+    /// Audit the resolved packages of the workspace.
+    ///
+    /// Each package from a registry is compared against the advisories for
+    /// packages of the same name which were neither withdrawn nor ignored.
+    /// If requested, packages are reported if the sparse index of their
+    /// registry lists their version as yanked, as well.  The findings name the
+    /// chain of dependencies pulling the package in.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let files = self.advisories();
+
+        if files.is_empty() {
+            findings.error(format!(
+                "no advisories found in '{}'",
+                self.database.display()
+            ));
+            return findings;
+        }
+
+        let mut advisories = std::collections::HashMap::<String, Vec<Advisory>>::new();
+
+        for advisory in files.iter().filter_map(|file| Advisory::read(file)) {
+            if advisory.advisory.withdrawn.is_none() && !self.ignore.contains(&advisory.advisory.id)
+            {
+                advisories
+                    .entry(advisory.advisory.package.clone())
+                    .or_default()
+                    .push(advisory);
+            }
+        }
+
+        let packages = self
+            .metadata
+            .packages()
+            .iter()
+            .filter(|package| {
+                package.source().is_some_and(|source| {
+                    source.starts_with("registry+") || source.starts_with("sparse+")
+                })
+            })
+            .collect::<Vec<&crate::Package>>();
+        let yanked = if self.yanked {
+            self.yanked(&packages)
+        } else {
+            std::collections::HashSet::new()
+        };
+
+        for package in packages {
+            let Ok(version) = semver::Version::parse(package.version()) else {
+                continue;
+            };
+            let path = self
+                .metadata
+                .dependency_path(package.id())
+                .iter()
+                .map(|package| package.name())
+                .collect::<Vec<&str>>();
+            let via = if path.len() > 1 {
+                format!("; via {}", path.join(" → "))
+            } else {
+                String::new()
+            };
+
+            for advisory in advisories
+                .get(package.name())
+                .into_iter()
+                .flatten()
+                .filter(|advisory| advisory.affects(&version))
+            {
+                self.record(
+                    &mut findings,
+                    advisory.kind(),
+                    format!(
+                        "{} {} {version}:  {} ({}{via})",
+                        advisory.advisory.id,
+                        package.name(),
+                        advisory.advisory.title.as_deref().unwrap_or("no title"),
+                        advisory.kind()
+                    ),
+                );
+            }
+
+            if yanked.contains(package.id()) {
+                self.record(
+                    &mut findings,
+                    "yanked",
+                    format!(
+                        "{} {version}:  yanked from the registry (yanked{via})",
+                        package.name()
+                    ),
+                );
+            }
+        }
+
+        findings
+    }
+
+    /// The identifiers of the given packages whose releases are yanked.
+    ///
+    /// The sparse index files of the packages are downloaded in parallel by
+    /// `curl` into the target directory.  Packages from registries without a
+    /// sparse index, as well as those whose index file cannot be downloaded,
+    /// are not considered yanked.  Since the index is not part of the inputs,
+    /// newly yanked releases are only found once the workspace changed or the
+    /// cache is bypassed.
+    fn yanked(&self, packages: &[&crate::Package]) -> std::collections::HashSet<String> {
+        let directory = self
+            .metadata
+            .workspace()
+            .target()
+            .join("optimise")
+            .join("audit");
+        let mut files = std::collections::BTreeMap::<String, std::path::PathBuf>::new();
+        let urls = packages
+            .iter()
+            .filter_map(|package| {
+                let url = Self::url(package.source()?, package.name())?;
+                let count = files.len();
+                let file = files
+                    .entry(url)
+                    .or_insert_with(|| directory.join(count.to_string()))
+                    .clone();
+                Some((package.id(), package.version(), file))
+            })
+            .collect::<Vec<(&str, &str, std::path::PathBuf)>>();
+
+        let _ = std::fs::remove_dir_all(&directory);
+
+        if files.is_empty() || std::fs::create_dir_all(&directory).is_err() {
+            return std::collections::HashSet::new();
+        }
+
+        // Failed downloads are not written such that the exit code is ignored.
+        let _ = crate::Process::new(
+            "curl",
+            ["--silent", "--fail", "--compressed", "--parallel"]
+                .into_iter()
+                .map(String::from)
+                .chain(files.iter().flat_map(|(url, file)| {
+                    [
+                        "--output".into(),
+                        file.to_string_lossy().into(),
+                        url.clone(),
+                    ]
+                }))
+                .collect(),
+            crate::Verbosity::Silent,
+        )
+        .run();
+
+        urls.into_iter()
+            .filter(|(_, version, file)| {
+                std::fs::read_to_string(file)
+                    .unwrap_or_default()
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+                    .any(|entry| entry.yanked && entry.vers == *version)
+            })
+            .map(|(id, _, _)| id.into())
+            .collect()
+    }
+
+    /// The URL of the sparse index file of the given package, if any.
+    ///
+    /// The Git index of crates.io is mapped to its sparse index.  Other
+    /// registries are only supported by their sparse index.
+    fn url(source: &str, name: &str) -> Option<String> {
+        let index = if source == "registry+https://github.com/rust-lang/crates.io-index" {
+            "https://index.crates.io/"
+        } else {
+            source.strip_prefix("sparse+")?
+        };
+        let name = name.to_lowercase();
+        let prefix = match name.len() {
+            1 => "1".into(),
+            2 => "2".into(),
+            3 => format!("3/{}", &name[..1]),
+            _ => format!("{}/{}", &name[..2], &name[2..4]),
+        };
+
+        Some(format!("{}/{prefix}/{name}", index.trim_end_matches('/')))
+    }
+}
+
+impl std::fmt::Display for Audit {
+    /// Implements the `Display` trait.
+    ///
+    /// An audit will be formatted like a command line naming the database as
+    /// well as the denied kinds of findings, the ignored advisories and whether
+    /// yanked releases are queried.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "audit {}", self.database.display())?;
+
+        for kind in &self.deny {
+            write!(f, " --deny {kind}")?;
+        }
+
+        for id in &self.ignore {
+            write!(f, " --ignore {id}")?;
+        }
+
+        if self.yanked {
+            write!(f, " --yanked")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// An advisory in the Markdown format of the database.
+    const MARKDOWN: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2020-0071"
+package = "time"
+date = "2020-11-18"
+
+[versions]
+patched = [">= 0.2.23"]
+unaffected = ["=0.2.0", "=0.2.1", "=0.2.2", "=0.2.3", "=0.2.4", "=0.2.5", "=0.2.6"]
+```
+
+# Potential segfault in the time crate
+
+Details.
+"#;
+
+    /// Parse the given version.
+    fn version(version: &str) -> semver::Version {
+        semver::Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn affects() {
+        let advisory = super::Advisory::parse(MARKDOWN, true).unwrap();
+
+        assert!(advisory.affects(&version("0.1.45")));
+        assert!(advisory.affects(&version("0.2.22")));
+        assert!(!advisory.affects(&version("0.2.3")));
+        assert!(!advisory.affects(&version("0.2.23")));
+        assert!(!advisory.affects(&version("0.3.0")));
+    }
+
+    #[test]
+    fn parse_markdown() {
+        let advisory = super::Advisory::parse(MARKDOWN, true).unwrap();
+
+        assert_eq!(advisory.advisory.id, "RUSTSEC-2020-0071");
+        assert_eq!(advisory.advisory.package, "time");
+        assert_eq!(
+            advisory.advisory.title.as_deref(),
+            Some("Potential segfault in the time crate")
+        );
+        assert_eq!(advisory.kind(), "vulnerability");
+        assert!(advisory.advisory.withdrawn.is_none());
+    }
+
+    #[test]
+    fn parse_toml() {
+        let advisory = super::Advisory::parse(
+            r#"
+[advisory]
+id = "RUSTSEC-2016-0001"
+package = "term"
+title = "term is looking for a new maintainer"
+informational = "unmaintained"
+withdrawn = "2017-01-01"
+"#,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            advisory.advisory.title.as_deref(),
+            Some("term is looking for a new maintainer")
+        );
+        assert_eq!(advisory.kind(), "unmaintained");
+        assert!(advisory.advisory.withdrawn.is_some());
+        assert!(advisory.affects(&version("1.0.0")));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(super::Advisory::parse("# No front matter", true).is_none());
+        assert!(super::Advisory::parse("[advisory]\nid = 1", false).is_none());
+    }
+
+    #[test]
+    fn url() {
+        let crates_io = "registry+https://github.com/rust-lang/crates.io-index";
+
+        assert_eq!(
+            super::Audit::url(crates_io, "Serde_JSON").as_deref(),
+            Some("https://index.crates.io/se/rd/serde_json")
+        );
+        assert_eq!(
+            super::Audit::url("sparse+https://example.com/index/", "syn").as_deref(),
+            Some("https://example.com/index/3/s/syn")
+        );
+        assert_eq!(
+            super::Audit::url(crates_io, "cc").as_deref(),
+            Some("https://index.crates.io/2/cc")
+        );
+        assert!(super::Audit::url("registry+https://example.com/index", "syn").is_none());
+    }
+}
+
+/******************************************************************************/
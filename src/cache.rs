@@ -0,0 +1,341 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+use std::fmt::Write as _;
+
+/// The fingerprint cache of the optimisation steps.
+///
+/// A step which succeeded before with identical inputs does not need to be run
+/// again.  The inputs of a step are summarised by a fingerprint, which is a
+/// SHA-256 digest of
+///
+/// * the sources of the workspace, respecting the `.gitignore`,
+/// * the manifests and the `Cargo.lock`,
+/// * the version of the toolchain the step runs with,
+/// * the further files the step declares to depend on, and
+/// * the application and the arguments of the step.
+///
+/// The fingerprints of the successful steps are stored in the file `cache`
+/// within the directory `optimise/` in the target directory.
+pub struct Cache {
+    /// The file to store the fingerprints in.
+    file: std::path::PathBuf,
+
+    /// The version information of the toolchain.
+    toolchain: String,
+
+    /// The digest of the files of the workspace, once computed.
+    tree: std::sync::Mutex<Option<String>>,
+
+    /// The workspace whose sources are the inputs of the steps.
+    workspace: crate::Workspace,
+}
+
+impl Cache {
+    /// Whether the given step succeeded before with the given fingerprint.
+    #[must_use]
+    pub fn contains(&self, step: &dyn crate::Step, fingerprint: &str) -> bool {
+        self.entries().get(&Self::key(step)).map(String::as_str) == Some(fingerprint)
+    }
+
+    /// Compute the hexadecimal SHA-256 digest of the given parts.
+    ///
+    /// Each part is prefixed by its length such that the boundaries between
+    /// the parts are part of the digest, as well.
+    fn digest<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> String {
+        let mut hasher = <sha2::Sha256 as sha2::Digest>::new();
+
+        for part in parts {
+            sha2::Digest::update(&mut hasher, (part.len() as u64).to_le_bytes());
+            sha2::Digest::update(&mut hasher, part);
+        }
+
+        sha2::Digest::finalize(hasher)
+            .iter()
+            .fold(String::new(), |mut digest, byte| {
+                let _ = write!(digest, "{byte:02x}");
+                digest
+            })
+    }
+
+    /// Read all stored fingerprints.
+    ///
+    /// Malformed lines as well as a missing cache file are silently skipped
+    /// since they just cause the affected steps to be run again.
+    fn entries(&self) -> std::collections::BTreeMap<String, String> {
+        std::fs::read_to_string(&self.file)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let (key, fingerprint) = line.split_once(' ')?;
+                Some((key.into(), fingerprint.into()))
+            })
+            .collect()
+    }
+
+    /// Compute the current fingerprint of the inputs of the given step.
+    ///
+    /// The files of the workspace and `Cargo.lock` are only read once until
+    /// the cache is refreshed, see `refresh`.  The further files the step
+    /// declares to depend on are read each time.
+    #[must_use]
+    pub fn fingerprint(&self, step: &dyn crate::Step) -> String {
+        let tree = self.tree();
+        let mut inputs = step.inputs();
+        inputs.sort();
+        inputs.dedup();
+
+        let inputs = inputs
+            .iter()
+            .map(|file| {
+                (
+                    file.to_string_lossy().to_string(),
+                    std::fs::read(file).unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<(String, Vec<u8>)>>();
+
+        Self::digest(
+            [
+                self.toolchain.as_bytes(),
+                step.identity().as_bytes(),
+                tree.as_bytes(),
+            ]
+            .into_iter()
+            .chain(
+                inputs
+                    .iter()
+                    .flat_map(|(file, content)| [file.as_bytes(), content.as_slice()]),
+            ),
+        )
+    }
+
+    /// The key to identify the given step within the cache file.
+    fn key(step: &dyn crate::Step) -> String {
+        Self::digest([step.identity().as_bytes()])
+    }
+
+    /// Configure the cache for the given workspace.
+    ///
+    /// The version information of the toolchain is queried from `rustc` once
+    /// such that it does not need to be determined for each step.  It is the
+    /// toolchain which is active in the workspace root.
+    #[must_use]
+    pub fn new(workspace: &crate::Workspace) -> Self {
+        Self::with_toolchain(workspace, None)
+    }
+
+    /// Forget the digest of the files of the workspace.
+    ///
+    /// The digest will be computed again for the next fingerprint.  This is
+    /// required whenever the files might have changed, such as at the start
+    /// of a run or after a mutating step.
+    pub fn refresh(&self) {
+        if let Ok(mut tree) = self.tree.lock() {
+            *tree = None;
+        }
+    }
+
+    /// Remember that the given step succeeded with the given fingerprint.
+    ///
+    /// # Errors
+    ///
+    /// If the cache file cannot be written, the underlying I/O error will be
+    /// returned.
+    pub fn store(&self, step: &dyn crate::Step, fingerprint: &str) -> std::io::Result<()> {
+        let mut entries = self.entries();
+        entries.insert(Self::key(step), fingerprint.into());
+
+        if let Some(directory) = self.file.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&self.file)?);
+
+        for (key, fingerprint) in entries {
+            std::io::Write::write_fmt(&mut file, format_args!("{key} {fingerprint}\n"))?;
+        }
+
+        std::io::Write::flush(&mut file)
+    }
+
+    /// Derive a cache for the steps running with the given toolchain.
+    ///
+    /// The fingerprints are stored in the same file.  They differ from those
+    /// of the default toolchain by the version information of the toolchain.
+    #[must_use]
+    pub fn toolchain(&self, toolchain: &str) -> Self {
+        Self::with_toolchain(&self.workspace, Some(toolchain))
+    }
+
+    /// The digest of the files of the workspace and `Cargo.lock`.
+    ///
+    /// The digest is computed once until the cache is refreshed.  Steps asking
+    /// for it in parallel wait for the first one to compute it.
+    fn tree(&self) -> String {
+        let compute = || {
+            let mut files = self.workspace.files();
+            files.push(self.workspace.root().join("Cargo.lock"));
+            files.sort();
+            files.dedup();
+
+            let files = files
+                .iter()
+                .map(|file| {
+                    (
+                        file.strip_prefix(self.workspace.root())
+                            .unwrap_or(file)
+                            .to_string_lossy()
+                            .to_string(),
+                        std::fs::read(file).unwrap_or_default(),
+                    )
+                })
+                .collect::<Vec<(String, Vec<u8>)>>();
+
+            Self::digest(
+                files
+                    .iter()
+                    .flat_map(|(file, content)| [file.as_bytes(), content.as_slice()]),
+            )
+        };
+
+        self.tree.lock().map_or_else(
+            |_| compute(),
+            |mut tree| tree.get_or_insert_with(compute).clone(),
+        )
+    }
+
+    /// Configure the cache for the given workspace and toolchain.
+    ///
+    /// If no toolchain is given, the one active in the workspace root is used.
+    fn with_toolchain(workspace: &crate::Workspace, toolchain: Option<&str>) -> Self {
+        let mut rustc = crate::Process::new(
+            "rustc",
+            toolchain
+                .map(|toolchain| format!("+{toolchain}"))
+                .into_iter()
+                .chain(["-vV".into()])
+                .collect(),
+            crate::Verbosity::Silent,
+        )
+        .current_dir(workspace.root());
+        let toolchain = match rustc.run() {
+            sysexits::ExitCode::Ok if rustc.success() => rustc.stdout().to_string(),
+            _ => String::new(),
+        };
+
+        Self {
+            file: workspace.target().join("optimise").join("cache"),
+            toolchain,
+            tree: std::sync::Mutex::new(None),
+            workspace: workspace.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// A step with a fixed label and no effect.
+    struct Noop(&'static str);
+
+    impl std::fmt::Display for Noop {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "noop {}", self.0)
+        }
+    }
+
+    impl crate::Step for Noop {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn run(&self, _: &crate::Context) -> crate::StepOutcome {
+            crate::StepOutcome::new(self.0, crate::Status::Passed)
+        }
+    }
+
+    /// Create an empty workspace in a fresh temporary directory.
+    fn workspace(name: &str) -> crate::Workspace {
+        let directory =
+            std::env::temp_dir().join(format!("optimise-cache-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("root").join("src")).unwrap();
+        std::fs::write(directory.join("root").join("src").join("lib.rs"), "").unwrap();
+        crate::Workspace::new(&directory.join("root"), &directory.join("target"))
+    }
+
+    #[test]
+    fn hit() {
+        let cache = super::Cache::new(&workspace("hit"));
+        let step = Noop("first");
+        let fingerprint = cache.fingerprint(&step);
+
+        assert!(!cache.contains(&step, &fingerprint));
+        cache.store(&step, &fingerprint).unwrap();
+        assert!(cache.contains(&step, &fingerprint));
+        assert!(cache.contains(&step, &cache.fingerprint(&step)));
+    }
+
+    #[test]
+    fn identity() {
+        let cache = super::Cache::new(&workspace("identity"));
+        let step = |target: &str| {
+            crate::ProcessStep::internal(
+                "cross",
+                crate::Check::Cross(crate::Cross::new(target, Vec::new())),
+                sysexits::ExitCode::DataErr,
+                crate::Verbosity::Silent,
+            )
+        };
+        let (first, second) = (step("wasm32-unknown-unknown"), step("x86_64-pc-windows-gnu"));
+
+        assert_eq!(first.to_string(), second.to_string());
+        assert_ne!(cache.fingerprint(&first), cache.fingerprint(&second));
+    }
+
+    #[test]
+    fn invalidation() {
+        let workspace = workspace("invalidation");
+        let cache = super::Cache::new(&workspace);
+        let step = Noop("first");
+        let fingerprint = cache.fingerprint(&step);
+        cache.store(&step, &fingerprint).unwrap();
+
+        std::fs::write(workspace.root().join("src").join("lib.rs"), "fn f() {}").unwrap();
+        assert!(cache.contains(&step, &cache.fingerprint(&step)));
+
+        cache.refresh();
+        assert!(!cache.contains(&step, &cache.fingerprint(&step)));
+    }
+
+    #[test]
+    fn miss() {
+        let cache = super::Cache::new(&workspace("miss"));
+        let first = Noop("first");
+        let second = Noop("second");
+        cache.store(&first, &cache.fingerprint(&first)).unwrap();
+
+        assert_ne!(cache.fingerprint(&first), cache.fingerprint(&second));
+        assert!(!cache.contains(&second, &cache.fingerprint(&second)));
+        assert!(!cache.contains(&second, &cache.fingerprint(&first)));
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,452 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A validation of a changelog in the style of Keep a Changelog.
+///
+/// The changelog is expected to consist of one level two heading per release,
+/// either `[Unreleased]` or `[<version>] - <YYYY-MM-DD>`, with the newest one
+/// on top.  Each release lists its changes in level three headings of the
+/// allowed section names.  HTML comments, such as a license header, are
+/// skipped.
+#[derive(Clone)]
+pub struct Changelog {
+    /// The Git reference to compare the sources to, if any.
+    base: Option<String>,
+
+    /// The changelog to validate.
+    file: std::path::PathBuf,
+
+    /// The information about the workspace the changelog belongs to.
+    metadata: std::sync::Arc<crate::Metadata>,
+
+    /// The patterns of the names of the release branches.
+    release_branches: Vec<String>,
+
+    /// The allowed names of the sections of a release.
+    sections: Vec<String>,
+}
+
+/// A release of the changelog.
+struct Release<'a> {
+    /// The line number of the heading of the release.
+    line: usize,
+
+    /// The number of lines describing changes.
+    entries: usize,
+
+    /// Whether this is the section `[Unreleased]`.
+    unreleased: bool,
+
+    /// The version of the release, if valid.
+    version: Option<&'a str>,
+}
+
+impl Changelog {
+    /// Run `git` with the given arguments and return its output on success.
+    fn git(&self, arguments: &[&str]) -> Option<String> {
+        let mut arguments = arguments
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+        arguments.splice(
+            0..0,
+            [
+                "-C".into(),
+                self.metadata.workspace().root().to_string_lossy().into(),
+            ],
+        );
+
+        let mut process = crate::Process::new("git", arguments, crate::Verbosity::Silent);
+
+        (process.run().is_success() && process.success()).then(|| process.stdout().into())
+    }
+
+    /// The files outside of the workspace the outcome of the validation depends
+    /// on.
+    ///
+    /// Since the validation depends on the checked out branch, the Git `HEAD`
+    /// is considered, as well.  If a base reference is configured, the files
+    /// storing the commits the base and the branch resolve to are added such
+    /// that moving either of them invalidates the outcome.
+    #[must_use]
+    pub fn inputs(&self) -> Vec<std::path::PathBuf> {
+        let workspace = self.metadata.workspace();
+        let root = workspace.root();
+        let mut references = vec!["HEAD".to_string()];
+
+        if let Some(base) = &self.base {
+            references.push("packed-refs".into());
+            references.extend(
+                self.git(&["rev-parse", "--symbolic-full-name", base, "HEAD"])
+                    .unwrap_or_default()
+                    .lines()
+                    .filter(|reference| reference.starts_with("refs/"))
+                    .map(Into::into),
+            );
+        }
+
+        let arguments = references
+            .iter()
+            .flat_map(|reference| ["--git-path", reference.as_str()])
+            .collect::<Vec<&str>>();
+
+        self.git(&[&["rev-parse"], arguments.as_slice()].concat())
+            .map_or_else(
+                || vec![root.join(".git").join("HEAD")],
+                |paths| paths.lines().map(|path| root.join(path)).collect(),
+            )
+    }
+
+    /// Configure a new validation of the given changelog.
+    #[must_use]
+    pub fn new(
+        config: &crate::ChangelogConfig,
+        file: &std::path::Path,
+        metadata: std::sync::Arc<crate::Metadata>,
+    ) -> Self {
+        Self {
+            base: config.base().map(Into::into),
+            file: file.into(),
+            metadata,
+            release_branches: config.release_branches().to_vec(),
+            sections: config.sections().to_vec(),
+        }
+    }
+
+    /// Parse the releases of the changelog, reporting structural errors.
+    fn parse<'a>(&self, contents: &'a str, findings: &mut crate::Findings) -> Vec<Release<'a>> {
+        let mut comment = false;
+        let mut releases = Vec::<Release>::new();
+        let mut sections = Vec::<&str>::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let number = index + 1;
+
+            if comment || line.trim_start().starts_with("<!--") {
+                comment = !line.contains("-->");
+                continue;
+            }
+
+            if let Some(heading) = line.strip_prefix("## ") {
+                sections.clear();
+
+                let unreleased = heading.trim() == "[Unreleased]";
+                let version = if unreleased {
+                    if !releases.is_empty() {
+                        findings.error(format!(
+                            "line {number}:  `[Unreleased]` is not the first release"
+                        ));
+                    }
+
+                    None
+                } else {
+                    Self::release(heading)
+                        .map_err(|error| findings.error(format!("line {number}:  {error}")))
+                        .ok()
+                };
+
+                releases.push(Release {
+                    entries: 0,
+                    line: number,
+                    unreleased,
+                    version,
+                });
+            } else if let Some(section) = line.strip_prefix("### ") {
+                let section = section.trim();
+
+                if releases.is_empty() {
+                    findings.error(format!(
+                        "line {number}:  section `{section}` outside of a release"
+                    ));
+                } else if !self.sections.iter().any(|allowed| allowed == section) {
+                    findings.error(format!("line {number}:  unknown section `{section}`"));
+                } else if sections.contains(&section) {
+                    findings.error(format!("line {number}:  duplicate section `{section}`"));
+                }
+
+                sections.push(section);
+            } else if !line.trim().is_empty() && !line.starts_with('#') {
+                if let Some(release) = releases.last_mut() {
+                    release.entries += 1;
+                }
+            }
+        }
+
+        releases
+    }
+
+    /// Parse the heading of a release, this is, its version and its date.
+    fn release(heading: &str) -> Result<&str, String> {
+        let Some((version, date)) = heading
+            .strip_prefix('[')
+            .and_then(|heading| heading.split_once("] - "))
+        else {
+            return Err(format!("malformed release heading `{heading}`"));
+        };
+
+        if semver::Version::parse(version).is_err() {
+            return Err(format!("invalid version `{version}`"));
+        }
+
+        let date = date.trim_end_matches("[YANKED]").trim();
+        let parts = date
+            .split('-')
+            .map(str::parse::<u32>)
+            .collect::<Result<Vec<u32>, _>>()
+            .unwrap_or_default();
+
+        match parts.as_slice() {
+            [_, 1..=12, 1..=31] if date.len() == 10 => Ok(version),
+            _ => Err(format!("invalid date `{date}` of version {version}")),
+        }
+    }
+
+    /// Validate the changelog.
+    ///
+    /// Besides the structure, the order of the versions is checked.  On a
+    /// release branch, the versions of the packages need to be listed.  If a
+    /// base reference is configured, the section `[Unreleased]` must not be
+    /// empty while files other than the changelog differ from the base.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let Ok(contents) = std::fs::read_to_string(&self.file) else {
+            findings.error(format!("failed to read '{}'", self.file.display()));
+            return findings;
+        };
+        let releases = self.parse(&contents, &mut findings);
+        let versions = releases
+            .iter()
+            .filter_map(|release| {
+                Some((release.line, semver::Version::parse(release.version?).ok()?))
+            })
+            .collect::<Vec<(usize, semver::Version)>>();
+
+        for pair in versions.windows(2) {
+            if pair[0].1 <= pair[1].1 {
+                findings.error(format!(
+                    "line {}:  version {} is not older than the preceding {}",
+                    pair[1].0, pair[1].1, pair[0].1
+                ));
+            }
+        }
+
+        self.validate_release(&releases, &mut findings);
+        self.validate_unreleased(&releases, &mut findings);
+        findings
+    }
+
+    /// Validate that the versions of the packages are listed on a release
+    /// branch.
+    ///
+    /// The package at the workspace root is considered, if any, otherwise all
+    /// members are.
+    fn validate_release(&self, releases: &[Release], findings: &mut crate::Findings) {
+        let Some(branch) = self.git(&["rev-parse", "--abbrev-ref", "HEAD"]) else {
+            return;
+        };
+        let branch = branch.trim();
+
+        if !self.release_branches.iter().any(|pattern| {
+            pattern
+                .strip_suffix('*')
+                .map_or(pattern == branch, |prefix| branch.starts_with(prefix))
+        }) {
+            return;
+        }
+
+        let root = self.metadata.workspace().root().join("Cargo.toml");
+        let members = self.metadata.members();
+        let packages = members
+            .iter()
+            .filter(|package| package.manifest_path() == root)
+            .collect::<Vec<_>>();
+        let packages = if packages.is_empty() {
+            members.iter().collect()
+        } else {
+            packages
+        };
+
+        for package in packages {
+            if !releases
+                .iter()
+                .any(|release| release.version == Some(package.version()))
+            {
+                findings.error(format!(
+                    "version {} of {} is not listed on the release branch `{branch}`",
+                    package.version(),
+                    package.name()
+                ));
+            }
+        }
+    }
+
+    /// Validate that the section `[Unreleased]` lists changes if the sources
+    /// differ from the base reference.
+    ///
+    /// The commits of the branch since its merge base with the reference are
+    /// compared.  The paths are taken relative to the workspace root, just
+    /// like the changelog.
+    fn validate_unreleased(&self, releases: &[Release], findings: &mut crate::Findings) {
+        let Some(base) = &self.base else {
+            return;
+        };
+        let range = format!("{base}...HEAD");
+        let Some(changes) = self.git(&["diff", "--name-only", "--relative", &range]) else {
+            findings.warning(format!("failed to compare the sources to `{base}`"));
+            return;
+        };
+        let workspace = self.metadata.workspace();
+        let root = workspace
+            .root()
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.root().into());
+        let file = self
+            .file
+            .canonicalize()
+            .unwrap_or_else(|_| self.file.clone());
+        let changelog = file.strip_prefix(&root).unwrap_or(&file);
+
+        if !changes
+            .lines()
+            .any(|path| std::path::Path::new(path) != changelog)
+        {
+            return;
+        }
+
+        match releases.iter().find(|release| release.unreleased) {
+            Some(release) if release.entries > 0 => {}
+            Some(release) => findings.error(format!(
+                "line {}:  `[Unreleased]` is empty although the sources differ from `{base}`",
+                release.line
+            )),
+            None => findings.error(format!(
+                "`[Unreleased]` is missing although the sources differ from `{base}`"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Changelog {
+    /// Implements the `Display` trait.
+    ///
+    /// A validation will be formatted like a command line naming the changelog
+    /// as well as the base reference.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "changelog {}", self.file.display())?;
+
+        if let Some(base) = &self.base {
+            write!(f, " --base {base}")?;
+        }
+
+        for pattern in &self.release_branches {
+            write!(f, " --release-branch '{pattern}'")?;
+        }
+
+        for section in &self.sections {
+            write!(f, " --section {section}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Changelog;
+
+    fn changelog() -> Changelog {
+        let metadata = serde_json::from_str(
+            r#"{
+                "packages": [],
+                "resolve": null,
+                "target_directory": "target",
+                "workspace_members": [],
+                "workspace_root": "."
+            }"#,
+        )
+        .unwrap();
+
+        Changelog::new(
+            &crate::ChangelogConfig::default(),
+            std::path::Path::new("CHANGELOG.md"),
+            std::sync::Arc::new(metadata),
+        )
+    }
+
+    #[test]
+    fn parse_unreleased() {
+        let mut findings = crate::Findings::default();
+        let releases = changelog().parse(
+            "<!-- ## [0.0.0] - comment -->\n\
+             # Changelog\n\
+             ## [Unreleased]\n\
+             ### Added\n\
+             * feature\n\
+             ## [0.1.0] - 2022-06-01\n\
+             ### Fixed\n\
+             * bug\n",
+            &mut findings,
+        );
+
+        assert!(findings.errors().is_empty());
+        assert_eq!(releases.len(), 2);
+        assert!(releases[0].unreleased);
+        assert_eq!(releases[0].entries, 1);
+        assert_eq!(releases[0].version, None);
+        assert!(!releases[1].unreleased);
+        assert_eq!(releases[1].version, Some("0.1.0"));
+    }
+
+    #[test]
+    fn parse_unreleased_not_first() {
+        let mut findings = crate::Findings::default();
+        let releases = changelog().parse(
+            "## [0.1.0] - 2022-06-01\n## [Unreleased]\n### Unknown\n",
+            &mut findings,
+        );
+
+        assert_eq!(releases.len(), 2);
+        assert_eq!(
+            findings.errors(),
+            [
+                "line 2:  `[Unreleased]` is not the first release",
+                "line 3:  unknown section `Unknown`"
+            ]
+        );
+    }
+
+    #[test]
+    fn release() {
+        assert_eq!(Changelog::release("[1.2.3] - 2022-06-01"), Ok("1.2.3"));
+        assert_eq!(
+            Changelog::release("[1.2.3] - 2022-06-01 [YANKED]"),
+            Ok("1.2.3")
+        );
+        assert!(Changelog::release("[Unreleased]").is_err());
+        assert!(Changelog::release("[Unreleased] - 2022-06-01").is_err());
+        assert!(Changelog::release("[1.2] - 2022-06-01").is_err());
+        assert!(Changelog::release("[1.2.3] - 2022-13-01").is_err());
+        assert!(Changelog::release("[1.2.3] - 22-06-01").is_err());
+        assert!(Changelog::release("1.2.3 - 2022-06-01").is_err());
+    }
+}
+
+/******************************************************************************/
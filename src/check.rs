@@ -0,0 +1,173 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The checks which are run in-process instead of spawning a process.
+#[derive(Clone)]
+pub enum Check {
+    /// Audit the dependencies against a local advisory database.
+    Audit(crate::Audit),
+
+    /// Validate the changelog.
+    Changelog(crate::Changelog),
+
+    /// Check the workspace for another target triple.
+    Cross(crate::Cross),
+
+    /// Report the packages resolved in multiple incompatible versions.
+    Duplicates(crate::Duplicates),
+
+    /// Check the combinations of the features of the members.
+    Features(crate::Features),
+
+    /// Check the license headers at the top of the source files.
+    Headers(crate::Headers),
+
+    /// Check the licenses of the dependencies against a policy.
+    Licenses(crate::Licenses),
+
+    /// Verify the minimum supported Rust versions of the members.
+    Msrv(crate::Msrv),
+
+    /// Check the public API of the libraries against a baseline.
+    Semver(crate::SemverCheck),
+
+    /// Detect the dependencies which are declared but never used.
+    Unused(crate::Unused),
+}
+
+impl Check {
+    /// The settings of this check, written like a command line.
+    ///
+    /// These identify the check within the fingerprint cache such that a
+    /// change of the configuration invalidates the cached outcome.
+    #[must_use]
+    pub fn identity(&self) -> String {
+        match self {
+            Self::Audit(audit) => audit.to_string(),
+            Self::Changelog(changelog) => changelog.to_string(),
+            Self::Cross(cross) => cross.to_string(),
+            Self::Duplicates(duplicates) => duplicates.to_string(),
+            Self::Features(features) => features.to_string(),
+            Self::Headers(headers) => headers.to_string(),
+            Self::Licenses(licenses) => licenses.to_string(),
+            Self::Msrv(msrv) => msrv.to_string(),
+            Self::Semver(semver) => semver.to_string(),
+            Self::Unused(unused) => unused.to_string(),
+        }
+    }
+
+    /// The files outside of the workspace the outcome of this check depends
+    /// on.
+    ///
+    /// These files are considered by the fingerprint cache in addition to the
+    /// files of the workspace.
+    #[must_use]
+    pub fn inputs(&self) -> Vec<std::path::PathBuf> {
+        match self {
+            Self::Audit(audit) => audit.inputs(),
+            Self::Changelog(changelog) => changelog.inputs(),
+            Self::Semver(semver) => semver.inputs(),
+            Self::Cross(_)
+            | Self::Duplicates(_)
+            | Self::Features(_)
+            | Self::Headers(_)
+            | Self::Licenses(_)
+            | Self::Msrv(_)
+            | Self::Unused(_) => Vec::new(),
+        }
+    }
+
+    /// Whether this check modifies the workspace.
+    #[must_use]
+    pub const fn is_mutating(&self) -> bool {
+        match self {
+            Self::Audit(_)
+            | Self::Changelog(_)
+            | Self::Cross(_)
+            | Self::Duplicates(_)
+            | Self::Features(_)
+            | Self::Msrv(_)
+            | Self::Semver(_) => false,
+            Self::Headers(headers) => headers.is_mutating(),
+            Self::Licenses(licenses) => licenses.is_mutating(),
+            Self::Unused(unused) => unused.is_mutating(),
+        }
+    }
+
+    /// The steps which need to succeed before this check is run.
+    ///
+    /// Most checks only depend on the state of the workspace.  The feature
+    /// combinations are only worth building once `cargo check` succeeded.
+    #[must_use]
+    pub const fn needs(&self) -> &'static [&'static str] {
+        match self {
+            Self::Features(_) => &["check"],
+            Self::Audit(_)
+            | Self::Changelog(_)
+            | Self::Cross(_)
+            | Self::Duplicates(_)
+            | Self::Headers(_)
+            | Self::Licenses(_)
+            | Self::Msrv(_)
+            | Self::Semver(_)
+            | Self::Unused(_) => &[],
+        }
+    }
+
+    /// Run this check.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        match self {
+            Self::Audit(audit) => audit.run(),
+            Self::Changelog(changelog) => changelog.run(),
+            Self::Cross(cross) => cross.run(),
+            Self::Duplicates(duplicates) => duplicates.run(),
+            Self::Features(features) => features.run(),
+            Self::Headers(headers) => headers.run(),
+            Self::Licenses(licenses) => licenses.run(),
+            Self::Msrv(msrv) => msrv.run(),
+            Self::Semver(semver) => semver.run(),
+            Self::Unused(unused) => unused.run(),
+        }
+    }
+}
+
+impl std::fmt::Display for Check {
+    /// Implements the `Display` trait.
+    ///
+    /// A check will be formatted as a short description of what it does.  The
+    /// settings of the check are not named, see `identity` for these.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Audit(_) => "audit the dependencies against an advisory database",
+            Self::Changelog(_) => "validate the changelog",
+            Self::Cross(_) => "check the workspace for another target triple",
+            Self::Duplicates(_) => "report the duplicate versions of the dependencies",
+            Self::Features(_) => "check the combinations of the features",
+            Self::Headers(_) => "check the license headers of the source files",
+            Self::Licenses(_) => "check the licenses of the dependencies",
+            Self::Msrv(_) => "verify the minimum supported Rust versions",
+            Self::Semver(_) => "check the public API against a baseline",
+            Self::Unused(_) => "detect the unused dependencies",
+        })
+    }
+}
+
+/******************************************************************************/
@@ -19,25 +19,148 @@
 \******************************************************************************/
 
 /// The configured command line options.
+#[allow(clippy::struct_excessive_bools)] // The flags are independent options.
 #[derive(clap::Parser)]
 #[clap(author, version, about, long_about = None)]
 pub struct CliOptions {
+    /// The mode to run this application in.
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// The configuration file to use instead of `optimise.toml`.
+    #[clap(short, long, value_parser, value_name = "PATH")]
+    config: Option<std::path::PathBuf>,
+
+    /// Find the lowest locally installed toolchain compiling the workspace.
+    #[clap(long, action)]
+    find_msrv: bool,
+
+    /// Let the checks supporting it fix their findings, as well.
+    #[clap(long, action)]
+    fix: bool,
+
     /// Show the license information and quit.
     #[clap(short, long, action)]
     license: bool,
 
+    /// Run all steps, even those whose inputs did not change.
+    #[clap(long, action)]
+    no_cache: bool,
+
+    /// Run only the given steps, see `list` for their names.
+    #[clap(long, value_parser, value_delimiter = ',', value_name = "STEPS")]
+    only: Vec<String>,
+
+    /// The profile selecting the steps to run.
+    #[clap(
+        short,
+        long,
+        default_value = "default",
+        value_parser,
+        value_name = "NAME"
+    )]
+    profile: String,
+
+    /// Skip the given steps, see `list` for their names.
+    #[clap(long, value_parser, value_delimiter = ',', value_name = "STEPS")]
+    skip: Vec<String>,
+
+    /// Run the non-mutating Cargo steps once per given toolchain.
+    #[clap(long, value_parser, value_delimiter = ',', value_name = "TOOLCHAINS")]
+    toolchains: Vec<String>,
+
+    /// Run the tests, as well, using `cargo nextest` if installed.
+    #[clap(long, action)]
+    test: bool,
+
+    /// Append a Markdown summary of the run to the given file.
+    #[clap(long, value_parser, value_name = "PATH")]
+    summary_markdown: Option<std::path::PathBuf>,
+
     /// The verbosity level for this run.
-    #[clap(short, long, default_value = "monosyllabic", value_parser)]
+    #[clap(
+        short,
+        long,
+        default_value = "monosyllabic",
+        global = true,
+        value_parser
+    )]
     verbosity: crate::Verbosity,
 }
 
 impl CliOptions {
+    /// Retrieve the mode to run this application in.
+    #[must_use]
+    pub const fn command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+
+    /// Retrieve the configuration file to use, if given.
+    #[must_use]
+    pub fn config(&self) -> Option<&std::path::Path> {
+        self.config.as_deref()
+    }
+
+    /// Retrieve whether the lowest compiling toolchain shall be found.
+    #[must_use]
+    pub const fn find_msrv(&self) -> bool {
+        self.find_msrv
+    }
+
+    /// Retrieve whether the checks shall fix their findings.
+    #[must_use]
+    pub const fn fix(&self) -> bool {
+        self.fix
+    }
+
     /// Retrieve the license information mode.
     #[must_use]
     pub const fn license(&self) -> bool {
         self.license
     }
 
+    /// Retrieve whether the fingerprint cache shall be ignored.
+    #[must_use]
+    pub const fn no_cache(&self) -> bool {
+        self.no_cache
+    }
+
+    /// Retrieve the names of the steps to run exclusively.
+    #[must_use]
+    pub fn only(&self) -> &[String] {
+        &self.only
+    }
+
+    /// Retrieve the name of the profile to use.
+    #[must_use]
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Retrieve the names of the steps to skip.
+    #[must_use]
+    pub fn skip(&self) -> &[String] {
+        &self.skip
+    }
+
+    /// Retrieve the file to append the Markdown summary to, if any.
+    #[must_use]
+    pub fn summary_markdown(&self) -> Option<&std::path::Path> {
+        self.summary_markdown.as_deref()
+    }
+
+    /// Retrieve whether the tests shall be run.
+    #[must_use]
+    pub const fn test(&self) -> bool {
+        self.test
+    }
+
+    /// Retrieve the toolchains to run the non-mutating steps with.
+    #[must_use]
+    pub fn toolchains(&self) -> &[String] {
+        &self.toolchains
+    }
+
     /// Retrieve the verbosity level.
     #[must_use]
     pub const fn verbosity(&self) -> crate::Verbosity {
@@ -45,4 +168,25 @@ impl CliOptions {
     }
 }
 
+/// The modes this application can be run in besides the default one.
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// Show the trends of the recorded runs.
+    History {
+        /// The number of most recent runs to consider.
+        #[clap(short, long, default_value = "10", value_parser)]
+        runs: usize,
+    },
+
+    /// List the names of the available steps.
+    List,
+
+    /// Re-run the non-mutating steps whenever the sources change.
+    Watch {
+        /// The milliseconds to wait for further changes before re-running.
+        #[clap(short, long, default_value = "500", value_parser)]
+        debounce: u64,
+    },
+}
+
 /******************************************************************************/
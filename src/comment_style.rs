@@ -0,0 +1,138 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The syntax of the comments to wrap a license header in.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum CommentStyle {
+    /// A block comment delimited by `/*` and `*/`.
+    #[serde(rename = "/*")]
+    Block,
+
+    /// A line comment starting with `#`.
+    #[serde(rename = "#")]
+    Hash,
+
+    /// A line comment starting with `//`.
+    #[serde(rename = "//")]
+    Line,
+
+    /// A markup comment delimited by `<!--` and `-->`.
+    #[serde(rename = "<!--")]
+    Markup,
+
+    /// No comment syntax since the template already contains it.
+    #[serde(rename = "verbatim")]
+    Verbatim,
+}
+
+impl CommentStyle {
+    /// The default comment style for files with the given extension, if any.
+    #[must_use]
+    pub fn for_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "c" | "cpp" | "css" | "h" | "hpp" | "java" | "js" | "ts" => Some(Self::Block),
+            "bash" | "py" | "sh" | "toml" | "yaml" | "yml" => Some(Self::Hash),
+            "rs" => Some(Self::Line),
+            "html" | "md" | "svg" | "xml" => Some(Self::Markup),
+            _ => None,
+        }
+    }
+
+    /// Wrap the given lines in comments of this style.
+    ///
+    /// Trailing whitespace is removed from the wrapped lines, except for the
+    /// verbatim style which keeps the lines as they are.
+    #[must_use]
+    pub fn wrap(self, lines: &[&str]) -> Vec<String> {
+        let prefixed = |prefix: &str| {
+            lines
+                .iter()
+                .map(|line| format!("{prefix} {line}").trim_end().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        match self {
+            Self::Block => std::iter::once("/*".into())
+                .chain(prefixed(" *"))
+                .chain(std::iter::once(" */".into()))
+                .collect(),
+            Self::Hash => prefixed("#"),
+            Self::Line => prefixed("//"),
+            Self::Markup => std::iter::once("<!--".into())
+                .chain(prefixed(" "))
+                .chain(std::iter::once("-->".into()))
+                .collect(),
+            Self::Verbatim => lines.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for CommentStyle {
+    /// Implements the `Display` trait.
+    ///
+    /// A comment style will be formatted by the token opening the comment or
+    /// as `verbatim`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let token = match self {
+            Self::Block => "/*",
+            Self::Hash => "#",
+            Self::Line => "//",
+            Self::Markup => "<!--",
+            Self::Verbatim => "verbatim",
+        };
+
+        write!(f, "{token}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn wrap_block() {
+        assert_eq!(
+            super::CommentStyle::Block.wrap(&["a", ""]),
+            ["/*", " * a", " *", " */"]
+        );
+    }
+
+    #[test]
+    fn wrap_line() {
+        assert_eq!(super::CommentStyle::Hash.wrap(&["a", ""]), ["# a", "#"]);
+        assert_eq!(super::CommentStyle::Line.wrap(&["a", ""]), ["// a", "//"]);
+    }
+
+    #[test]
+    fn wrap_markup() {
+        assert_eq!(
+            super::CommentStyle::Markup.wrap(&["a"]),
+            ["<!--", "  a", "-->"]
+        );
+    }
+
+    #[test]
+    fn wrap_verbatim() {
+        assert_eq!(
+            super::CommentStyle::Verbatim.wrap(&["## a  ##", "", "#  "]),
+            ["## a  ##", "", "#  "]
+        );
+    }
+}
+
+/******************************************************************************/
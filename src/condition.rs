@@ -0,0 +1,250 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A condition a step is only run under.
+///
+/// Conditions are evaluated against the workspace before the steps are run.
+/// Steps whose conditions do not hold are reported as skipped.
+#[derive(Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub enum Condition {
+    /// At least one of the given conditions holds.
+    Any(Vec<Self>),
+
+    /// The name of the checked out Git branch matches the given pattern.
+    ///
+    /// A trailing `*` matches arbitrary suffixes.
+    Branch(String),
+
+    /// A member of the workspace has a target of the given kind, such as
+    /// `lib`, `bin` or `proc-macro`.
+    CrateType(String),
+
+    /// The given environment variable is set.
+    Env(String),
+
+    /// A member of the workspace declares the given feature.
+    Feature(String),
+
+    /// The given file or directory relative to the workspace root exists.
+    File(std::path::PathBuf),
+
+    /// The workspace contains tests, or does not if `false` is given.
+    ///
+    /// Tests are test targets of the members, such as the files in `tests/`,
+    /// or functions with the `#[test]` attribute in the Rust sources.
+    Tests(bool),
+}
+
+impl Condition {
+    /// The name of the checked out Git branch, if any.
+    fn branch(workspace: &crate::Workspace) -> Option<String> {
+        let mut git = crate::Process::new(
+            "git",
+            vec![
+                "-C".into(),
+                workspace.root().to_string_lossy().into(),
+                "rev-parse".into(),
+                "--abbrev-ref".into(),
+                "HEAD".into(),
+            ],
+            crate::Verbosity::Silent,
+        );
+
+        (git.run().is_success() && git.success()).then(|| git.stdout().trim().to_string())
+    }
+
+    /// Whether this condition holds for the given workspace.
+    #[must_use]
+    pub fn holds(&self, metadata: &crate::Metadata) -> bool {
+        let workspace = metadata.workspace();
+
+        match self {
+            Self::Any(conditions) => conditions.iter().any(|condition| condition.holds(metadata)),
+            Self::Branch(pattern) => Self::branch(&workspace).is_some_and(|branch| {
+                pattern
+                    .strip_suffix('*')
+                    .map_or(*pattern == branch, |prefix| branch.starts_with(prefix))
+            }),
+            Self::CrateType(kind) => metadata
+                .members()
+                .iter()
+                .flat_map(|package| package.targets())
+                .any(|target| target.kind().contains(kind)),
+            Self::Env(variable) => std::env::var_os(variable).is_some(),
+            Self::Feature(feature) => metadata
+                .members()
+                .iter()
+                .any(|package| package.features().contains_key(feature)),
+            Self::File(path) => workspace.root().join(path).exists(),
+            Self::Tests(expected) => Self::tests(metadata) == *expected,
+        }
+    }
+
+    /// Whether the given workspace contains tests.
+    fn tests(metadata: &crate::Metadata) -> bool {
+        metadata
+            .members()
+            .iter()
+            .flat_map(|package| package.targets())
+            .any(|target| target.kind().iter().any(|kind| kind == "test"))
+            || metadata
+                .workspace()
+                .files()
+                .iter()
+                .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+                .any(|path| {
+                    std::fs::read_to_string(path).is_ok_and(|source| source.contains("#[test]"))
+                })
+    }
+}
+
+impl std::fmt::Display for Condition {
+    /// Implements the `Display` trait.
+    ///
+    /// A condition will be formatted like its notation in the configuration.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any(conditions) => write!(
+                f,
+                "any [{}]",
+                conditions
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Self::Branch(pattern) => write!(f, "branch = '{pattern}'"),
+            Self::CrateType(kind) => write!(f, "crate-type = '{kind}'"),
+            Self::Env(variable) => write!(f, "env = '{variable}'"),
+            Self::Feature(feature) => write!(f, "feature = '{feature}'"),
+            Self::File(path) => write!(f, "file = '{}'", path.display()),
+            Self::Tests(expected) => write!(f, "tests = {expected}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// The information about a workspace in a fresh temporary directory with
+    /// a library declaring the feature `extra`.
+    fn metadata(name: &str, source: &str) -> crate::Metadata {
+        let root =
+            std::env::temp_dir().join(format!("optimise-condition-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src").join("lib.rs"), source).unwrap();
+
+        serde_json::from_value(serde_json::json!({
+            "packages": [{
+                "dependencies": [],
+                "features": {"extra": []},
+                "id": "member 0.1.0",
+                "license": null,
+                "license_file": null,
+                "manifest_path": root.join("Cargo.toml"),
+                "name": "member",
+                "rust_version": null,
+                "source": null,
+                "targets": [{"doctest": true, "kind": ["lib"]}],
+                "version": "0.1.0"
+            }],
+            "resolve": null,
+            "target_directory": root.join("target"),
+            "workspace_members": ["member 0.1.0"],
+            "workspace_root": root
+        }))
+        .unwrap()
+    }
+
+    /// Parse the given condition in the notation of the configuration.
+    fn parse(condition: &str) -> super::Condition {
+        toml::from_str::<std::collections::BTreeMap<String, super::Condition>>(&format!(
+            "condition = {condition}"
+        ))
+        .unwrap()
+        .remove("condition")
+        .unwrap()
+    }
+
+    #[test]
+    fn display() {
+        for condition in [
+            "{ branch = 'release/*' }",
+            "{ crate-type = 'proc-macro' }",
+            "{ feature = 'extra' }",
+            "{ file = 'src/lib.rs' }",
+        ] {
+            let condition = parse(condition);
+            assert_eq!(
+                parse(&format!("{{ {condition} }}")).to_string(),
+                condition.to_string()
+            );
+        }
+
+        assert_eq!(
+            parse("{ any = [{ env = 'CI' }, { tests = false }] }").to_string(),
+            "any [env = 'CI', tests = false]"
+        );
+    }
+
+    #[test]
+    fn holds() {
+        let metadata = metadata("holds", "pub fn f() {}\n");
+
+        assert!(parse("{ crate-type = 'lib' }").holds(&metadata));
+        assert!(!parse("{ crate-type = 'bin' }").holds(&metadata));
+        assert!(parse("{ env = 'PATH' }").holds(&metadata));
+        assert!(!parse("{ env = 'OPTIMISE_UNDEFINED_VARIABLE' }").holds(&metadata));
+        assert!(parse("{ feature = 'extra' }").holds(&metadata));
+        assert!(!parse("{ feature = 'missing' }").holds(&metadata));
+        assert!(parse("{ file = 'src/lib.rs' }").holds(&metadata));
+        assert!(!parse("{ file = 'src/main.rs' }").holds(&metadata));
+        assert!(!parse("{ branch = '*' }").holds(&metadata));
+        assert!(parse("{ any = [{ file = 'missing' }, { feature = 'extra' }] }").holds(&metadata));
+        assert!(!parse("{ any = [] }").holds(&metadata));
+    }
+
+    #[test]
+    fn holds_tests() {
+        let with = metadata(
+            "tests",
+            "#[cfg(test)]\nmod tests {\n    #[test]\n    fn t() {}\n}\n",
+        );
+        let without = metadata("no-tests", "pub fn f() {}\n");
+
+        assert!(parse("{ tests = true }").holds(&with));
+        assert!(!parse("{ tests = false }").holds(&with));
+        assert!(!parse("{ tests = true }").holds(&without));
+        assert!(parse("{ tests = false }").holds(&without));
+    }
+
+    #[test]
+    fn parse_unknown() {
+        assert!(
+            toml::from_str::<std::collections::BTreeMap<String, super::Condition>>(
+                "condition = { os = 'linux' }"
+            )
+            .is_err()
+        );
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,818 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The settings read from the configuration file.
+///
+/// The configuration is read from `optimise.toml` in the workspace root, unless
+/// another file is passed on the command line.  All sections are optional and
+/// will fall back to their defaults if omitted.
+#[derive(Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The settings of the dependency audit.
+    audit: AuditConfig,
+
+    /// The settings of the changelog validation.
+    changelog: ChangelogConfig,
+
+    /// The user-defined commands to run as additional steps.
+    commands: Vec<CommandConfig>,
+
+    /// The settings of the checks for other target triples.
+    cross: CrossConfig,
+
+    /// The settings of the documentation step.
+    doc: DocConfig,
+
+    /// The settings of the report of duplicated packages.
+    duplicates: DuplicatesConfig,
+
+    /// The settings of the feature combinations, if they are checked.
+    features: Option<FeaturesConfig>,
+
+    /// The settings of the license headers, if they are checked.
+    headers: Option<HeadersConfig>,
+
+    /// The policy for the licenses of the dependencies, if they are checked.
+    licenses: Option<LicensesConfig>,
+
+    /// The discovered plugins to run unless the steps are selected otherwise.
+    plugins: Vec<String>,
+
+    /// The named profiles in addition to the built-in ones.
+    profiles: std::collections::BTreeMap<String, ProfileConfig>,
+
+    /// The settings of the semver check, if the libraries are checked.
+    semver: Option<SemverConfig>,
+
+    /// The settings of the detection of unused dependencies.
+    unused: UnusedConfig,
+
+    /// The conditions the named steps are only run under.
+    when: std::collections::BTreeMap<String, Vec<crate::Condition>>,
+}
+
+/// The settings of the dependency audit.
+///
+/// The audit is only run if the location of the advisory database is given.
+/// Relative paths are resolved against the workspace root.  A failed audit
+/// exits with `ExitCode::DataErr`, like the other checks.
+#[derive(serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    /// The directory containing a checkout of the `RustSec` advisory database.
+    database: Option<std::path::PathBuf>,
+
+    /// The kinds of findings letting the audit fail, such as `vulnerability`,
+    /// `unmaintained`, `unsound` or `yanked`.
+    deny: Vec<String>,
+
+    /// The identifiers of the advisories to ignore.
+    ignore: Vec<String>,
+
+    /// Whether to query the sparse registry index for yanked releases.
+    ///
+    /// Unlike the rest of the audit, this requires network access.
+    yanked: bool,
+}
+
+/// The settings of the changelog validation.
+///
+/// The changelog is only validated if the file exists.
+#[derive(serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChangelogConfig {
+    /// The Git reference to compare the sources to, if any.
+    base: Option<String>,
+
+    /// The changelog relative to the workspace root.
+    file: std::path::PathBuf,
+
+    /// The patterns of the names of the release branches.
+    ///
+    /// A trailing `*` matches arbitrary suffixes.
+    release_branches: Vec<String>,
+
+    /// The allowed names of the sections of a release.
+    sections: Vec<String>,
+}
+
+/// A user-defined command to run as an additional step.
+///
+/// The command is run in the root of the workspace.  Unless other expectations
+/// are given, it succeeds if it exits with code zero.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandConfig {
+    /// The command line arguments to pass.
+    #[serde(default)]
+    args: Vec<String>,
+
+    /// The expectations the command needs to meet in order to succeed.
+    #[serde(default)]
+    expect: crate::Expectation,
+
+    /// Whether the command modifies the sources of the project.
+    #[serde(default)]
+    mutating: bool,
+
+    /// The name of the step.
+    name: String,
+
+    /// The names of the steps which need to succeed before the command, if
+    /// declared.
+    needs: Option<Vec<String>>,
+
+    /// The application to call.
+    program: String,
+}
+
+/// The settings of the checks for other target triples.
+///
+/// Each target is checked in a step of its own, named after the triple.
+#[derive(Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrossConfig {
+    /// The target triples to check for.
+    targets: Vec<String>,
+}
+
+/// The settings of the documentation step.
+///
+/// Only warnings are denied by default.  Stricter lints, such as
+/// `missing_docs`, need to be opted into since they would fail most projects.
+/// The missing documentation is then summarised per kind of item.
+#[derive(serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DocConfig {
+    /// The rustdoc lints to deny.
+    deny: Vec<String>,
+}
+
+/// The settings of the report of duplicated packages.
+///
+/// Duplicates are only reported as warnings unless they are denied or their
+/// number exceeds the maximum.
+#[derive(Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DuplicatesConfig {
+    /// The names of the packages whose duplicates are never reported.
+    allow: Vec<String>,
+
+    /// The names of the packages which must not be duplicated.
+    deny: Vec<String>,
+
+    /// The number of duplicated packages to tolerate.
+    max: Option<usize>,
+}
+
+/// The settings of the feature combinations.
+///
+/// The combinations are only checked if this section is given.  Besides the
+/// default features, no features and all features, each feature is checked on
+/// its own.  With a depth, all combinations of up to that many features are
+/// checked instead.
+#[derive(Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeaturesConfig {
+    /// Whether to run Clippy instead of `cargo check` for each combination.
+    clippy: bool,
+
+    /// The maximum number of features to combine, if more than one.
+    depth: Option<usize>,
+
+    /// The names of the features which are never enabled.
+    exclude: Vec<String>,
+}
+
+/// The settings of the license headers.
+///
+/// The headers are only checked if this section is given.  The templates may
+/// contain the placeholders `{year}` and `{author}`.  Templates which already
+/// contain the comment syntax, such as boxed banners, are used as they are
+/// with the comment style `verbatim`.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeadersConfig {
+    /// The author to insert for the placeholder `{author}`.
+    #[serde(default)]
+    author: String,
+
+    /// The extensions of the files to check.
+    #[serde(default = "HeadersConfig::default_extensions")]
+    extensions: Vec<String>,
+
+    /// The comment styles overriding the defaults per extension.
+    #[serde(default)]
+    styles: std::collections::BTreeMap<String, crate::CommentStyle>,
+
+    /// The template of the header, unless given per extension.
+    #[serde(default)]
+    template: String,
+
+    /// The templates overriding the default one per extension.
+    #[serde(default)]
+    templates: std::collections::BTreeMap<String, String>,
+
+    /// The year to insert for the placeholder `{year}`, if not the current one.
+    year: Option<String>,
+}
+
+/// The policy for the licenses of the dependencies.
+///
+/// The licenses are only checked if this section is given.  Licenses which are
+/// neither allowed nor denied are considered a violation of the policy.
+#[derive(Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LicensesConfig {
+    /// The SPDX identifiers of the licenses to comply with.
+    allow: Vec<String>,
+
+    /// The SPDX identifiers of the licenses to never comply with.
+    deny: Vec<String>,
+
+    /// The names of the packages not to check.
+    ignore: Vec<String>,
+
+    /// The file to write the inventory of third-party licenses to, if any.
+    inventory: Option<std::path::PathBuf>,
+}
+
+/// A named selection of the steps to run.
+///
+/// A profile may extend another one, inheriting all settings it does not set
+/// itself.  The profiles `default`, `quick`, `ci` and `release` are built in
+/// but can be redefined.  Unlike on the command line, steps which are not
+/// configured are reported without failing, unless a profile restricts the
+/// run to steps of which none is configured.
+#[derive(Clone, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// The name of the profile to inherit the unset settings from, if any.
+    extends: Option<String>,
+
+    /// The names of the steps to run exclusively, if restricted.
+    only: Option<Vec<String>>,
+
+    /// The names of the steps to skip.
+    skip: Option<Vec<String>>,
+
+    /// Whether to run the tests, as well.
+    test: Option<bool>,
+}
+
+/// The settings of the semver check.
+///
+/// The libraries are only checked if this section is given.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SemverConfig {
+    /// The Git reference or the `.crate` file to compare to.
+    baseline: String,
+}
+
+/// The settings of the detection of unused dependencies.
+///
+/// Unused dependencies are only reported as warnings unless they are denied.
+#[derive(Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UnusedConfig {
+    /// The dependencies to never report, such as those only used by macros.
+    allow: Vec<String>,
+
+    /// Whether unused dependencies let the detection fail.
+    deny: bool,
+
+    /// Whether the compiler shall be asked for the unused dependencies.
+    precise: bool,
+}
+
+impl AuditConfig {
+    /// The directory containing a checkout of the `RustSec` advisory database.
+    #[must_use]
+    pub fn database(&self) -> Option<&std::path::Path> {
+        self.database.as_deref()
+    }
+
+    /// The kinds of findings letting the audit fail.
+    #[must_use]
+    pub fn deny(&self) -> &[String] {
+        &self.deny
+    }
+
+    /// The identifiers of the advisories to ignore.
+    #[must_use]
+    pub fn ignore(&self) -> &[String] {
+        &self.ignore
+    }
+
+    /// Whether to query the sparse registry index for yanked releases.
+    #[must_use]
+    pub const fn yanked(&self) -> bool {
+        self.yanked
+    }
+}
+
+impl Default for AuditConfig {
+    /// Only vulnerabilities let the audit fail by default.  Yanked releases
+    /// are not queried by default.
+    fn default() -> Self {
+        Self {
+            database: None,
+            deny: vec!["vulnerability".into()],
+            ignore: Vec::new(),
+            yanked: false,
+        }
+    }
+}
+
+impl ChangelogConfig {
+    /// The Git reference to compare the sources to, if any.
+    #[must_use]
+    pub fn base(&self) -> Option<&str> {
+        self.base.as_deref()
+    }
+
+    /// The changelog relative to the workspace root.
+    #[must_use]
+    pub fn file(&self) -> &std::path::Path {
+        &self.file
+    }
+
+    /// The patterns of the names of the release branches.
+    #[must_use]
+    pub fn release_branches(&self) -> &[String] {
+        &self.release_branches
+    }
+
+    /// The allowed names of the sections of a release.
+    #[must_use]
+    pub fn sections(&self) -> &[String] {
+        &self.sections
+    }
+}
+
+impl Default for ChangelogConfig {
+    /// Validate `CHANGELOG.md` with the sections of Keep a Changelog.
+    fn default() -> Self {
+        Self {
+            base: None,
+            file: "CHANGELOG.md".into(),
+            release_branches: vec!["release/*".into()],
+            sections: [
+                "Added",
+                "Changed",
+                "Deprecated",
+                "Removed",
+                "Fixed",
+                "Security",
+            ]
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        }
+    }
+}
+
+impl CommandConfig {
+    /// The command line arguments to pass.
+    #[must_use]
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// The expectations the command needs to meet in order to succeed.
+    #[must_use]
+    pub const fn expect(&self) -> &crate::Expectation {
+        &self.expect
+    }
+
+    /// Whether the command modifies the sources of the project.
+    #[must_use]
+    pub const fn mutating(&self) -> bool {
+        self.mutating
+    }
+
+    /// The name of the step.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the steps which need to succeed before the command, if
+    /// declared.
+    #[must_use]
+    pub fn needs(&self) -> Option<&[String]> {
+        self.needs.as_deref()
+    }
+
+    /// The application to call.
+    #[must_use]
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+}
+
+impl Config {
+    /// The settings of the dependency audit.
+    #[must_use]
+    pub const fn audit(&self) -> &AuditConfig {
+        &self.audit
+    }
+
+    /// The settings of the changelog validation.
+    #[must_use]
+    pub const fn changelog(&self) -> &ChangelogConfig {
+        &self.changelog
+    }
+
+    /// The user-defined commands to run as additional steps.
+    #[must_use]
+    pub fn commands(&self) -> &[CommandConfig] {
+        &self.commands
+    }
+
+    /// The names of the steps conditions are configured for.
+    #[must_use]
+    pub fn conditional(&self) -> Vec<&str> {
+        self.when.keys().map(String::as_str).collect()
+    }
+
+    /// The settings of the checks for other target triples.
+    #[must_use]
+    pub const fn cross(&self) -> &CrossConfig {
+        &self.cross
+    }
+
+    /// The settings of the documentation step.
+    #[must_use]
+    pub const fn doc(&self) -> &DocConfig {
+        &self.doc
+    }
+
+    /// The settings of the report of duplicated packages.
+    #[must_use]
+    pub const fn duplicates(&self) -> &DuplicatesConfig {
+        &self.duplicates
+    }
+
+    /// The settings of the feature combinations, if they are checked.
+    #[must_use]
+    pub const fn features(&self) -> Option<&FeaturesConfig> {
+        self.features.as_ref()
+    }
+
+    /// The settings of the license headers, if they are checked.
+    #[must_use]
+    pub const fn headers(&self) -> Option<&HeadersConfig> {
+        self.headers.as_ref()
+    }
+
+    /// The policy for the licenses of the dependencies, if they are checked.
+    #[must_use]
+    pub const fn licenses(&self) -> Option<&LicensesConfig> {
+        self.licenses.as_ref()
+    }
+
+    /// Read the configuration from the given file.
+    ///
+    /// A missing file is not considered an error, the default configuration
+    /// will be returned instead.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read or does not contain a valid configuration,
+    /// an explanation will be printed and `ExitCode::Config` will be returned.
+    pub fn load(file: &std::path::Path) -> Result<Self, sysexits::ExitCode> {
+        let contents = match std::fs::read_to_string(file) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(_) => {
+                eprintln!("Failed to read '{}'!", file.display());
+                return Err(sysexits::ExitCode::Config);
+            }
+        };
+
+        toml::from_str(&contents).map_err(|error| {
+            eprintln!("Invalid configuration in '{}':  {error}", file.display());
+            sysexits::ExitCode::Config
+        })
+    }
+
+    /// The discovered plugins to run unless the steps are selected otherwise.
+    #[must_use]
+    pub fn plugins(&self) -> &[String] {
+        &self.plugins
+    }
+
+    /// Resolve the profile with the given name.
+    ///
+    /// Profiles defined in the configuration take precedence over the built-in
+    /// ones of the same name.  The settings of the extended profiles are
+    /// inherited unless the extending profile sets them itself.
+    ///
+    /// # Errors
+    ///
+    /// If the profile or one of the profiles it extends is not defined, or if
+    /// a profile extends itself, an explanation will be returned.
+    pub fn profile(&self, name: &str) -> Result<ProfileConfig, String> {
+        let mut chain = Vec::<String>::new();
+        let mut profiles = Vec::new();
+        let mut current = Some(name.to_string());
+
+        while let Some(name) = current.take() {
+            if chain.contains(&name) {
+                return Err(format!("profile '{name}' extends itself"));
+            }
+
+            let profile = self
+                .profiles
+                .get(&name)
+                .cloned()
+                .or_else(|| ProfileConfig::builtin(&name))
+                .ok_or_else(|| format!("there is no profile named '{name}'"))?;
+
+            current.clone_from(&profile.extends);
+            chain.push(name);
+            profiles.push(profile);
+        }
+
+        Ok(profiles
+            .into_iter()
+            .rev()
+            .fold(ProfileConfig::default(), |base, profile| ProfileConfig {
+                extends: None,
+                only: profile.only.or(base.only),
+                skip: profile.skip.or(base.skip),
+                test: profile.test.or(base.test),
+            }))
+    }
+
+    /// The settings of the semver check, if the libraries are checked.
+    #[must_use]
+    pub const fn semver(&self) -> Option<&SemverConfig> {
+        self.semver.as_ref()
+    }
+
+    /// The settings of the detection of unused dependencies.
+    #[must_use]
+    pub const fn unused(&self) -> &UnusedConfig {
+        &self.unused
+    }
+
+    /// The conditions the step with the given name is only run under.
+    ///
+    /// All of the conditions need to hold for the step to be run.
+    #[must_use]
+    pub fn when(&self, step: &str) -> &[crate::Condition] {
+        self.when.get(step).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl CrossConfig {
+    /// The target triples to check for.
+    #[must_use]
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+}
+
+impl DocConfig {
+    /// Compose the `RUSTDOCFLAGS` denying the configured lints.
+    ///
+    /// Flags which are already set in the environment are kept such that they
+    /// can still be adjusted by the user.
+    #[must_use]
+    pub fn rustdocflags(&self) -> String {
+        std::env::var("RUSTDOCFLAGS")
+            .ok()
+            .filter(|flags| !flags.trim().is_empty())
+            .into_iter()
+            .chain(self.deny.iter().map(|lint| format!("-D {lint}")))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl Default for DocConfig {
+    /// Deny all warnings by default.
+    fn default() -> Self {
+        Self {
+            deny: vec!["warnings".into()],
+        }
+    }
+}
+
+impl DuplicatesConfig {
+    /// The names of the packages whose duplicates are never reported.
+    #[must_use]
+    pub fn allow(&self) -> &[String] {
+        &self.allow
+    }
+
+    /// The names of the packages which must not be duplicated.
+    #[must_use]
+    pub fn deny(&self) -> &[String] {
+        &self.deny
+    }
+
+    /// The number of duplicated packages to tolerate, if limited.
+    #[must_use]
+    pub const fn max(&self) -> Option<usize> {
+        self.max
+    }
+}
+
+impl FeaturesConfig {
+    /// Whether to run Clippy instead of `cargo check` for each combination.
+    #[must_use]
+    pub const fn clippy(&self) -> bool {
+        self.clippy
+    }
+
+    /// The maximum number of features to combine, if more than one.
+    #[must_use]
+    pub const fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// The names of the features which are never enabled.
+    #[must_use]
+    pub fn exclude(&self) -> &[String] {
+        &self.exclude
+    }
+}
+
+impl HeadersConfig {
+    /// The author to insert for the placeholder `{author}`.
+    #[must_use]
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// Only Rust sources are checked by default.
+    fn default_extensions() -> Vec<String> {
+        vec!["rs".into()]
+    }
+
+    /// The extensions of the files to check.
+    #[must_use]
+    pub fn extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    /// The comment styles overriding the defaults per extension.
+    #[must_use]
+    pub const fn styles(&self) -> &std::collections::BTreeMap<String, crate::CommentStyle> {
+        &self.styles
+    }
+
+    /// The template of the header, unless given per extension.
+    #[must_use]
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// The templates overriding the default one per extension.
+    #[must_use]
+    pub const fn templates(&self) -> &std::collections::BTreeMap<String, String> {
+        &self.templates
+    }
+
+    /// The year to insert for the placeholder `{year}`, if not the current one.
+    #[must_use]
+    pub fn year(&self) -> Option<&str> {
+        self.year.as_deref()
+    }
+}
+
+impl LicensesConfig {
+    /// The SPDX identifiers of the licenses to comply with.
+    #[must_use]
+    pub fn allow(&self) -> &[String] {
+        &self.allow
+    }
+
+    /// The SPDX identifiers of the licenses to never comply with.
+    #[must_use]
+    pub fn deny(&self) -> &[String] {
+        &self.deny
+    }
+
+    /// The names of the packages not to check.
+    #[must_use]
+    pub fn ignore(&self) -> &[String] {
+        &self.ignore
+    }
+
+    /// The file to write the inventory of third-party licenses to, if any.
+    #[must_use]
+    pub fn inventory(&self) -> Option<&std::path::Path> {
+        self.inventory.as_deref()
+    }
+}
+
+impl ProfileConfig {
+    /// The built-in profile with the given name, if any.
+    ///
+    /// * `default` runs all configured steps but `fmt-check`, which would be
+    ///   redundant after `fmt`,
+    /// * `quick` only formats and checks the sources,
+    /// * `ci` runs all steps which do not modify the sources, including
+    ///   `fmt-check` instead of `fmt`, together with the tests, and
+    /// * `release` validates the changelog, the licenses and the public API.
+    fn builtin(name: &str) -> Option<Self> {
+        let steps = |names: &[&str]| Some(names.iter().map(ToString::to_string).collect());
+
+        match name {
+            "default" => Some(Self {
+                skip: steps(&["fmt-check"]),
+                ..Self::default()
+            }),
+            "quick" => Some(Self {
+                extends: Some("default".into()),
+                only: steps(&["fmt", "check"]),
+                ..Self::default()
+            }),
+            "ci" => Some(Self {
+                extends: Some("default".into()),
+                skip: steps(&["fix", "fmt"]),
+                test: Some(true),
+                ..Self::default()
+            }),
+            "release" => Some(Self {
+                extends: Some("default".into()),
+                only: steps(&["changelog", "licenses", "semver"]),
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// The names of the steps to run exclusively, if restricted.
+    #[must_use]
+    pub fn only(&self) -> &[String] {
+        self.only.as_deref().unwrap_or_default()
+    }
+
+    /// The names of the steps to skip.
+    #[must_use]
+    pub fn skip(&self) -> &[String] {
+        self.skip.as_deref().unwrap_or_default()
+    }
+
+    /// Whether to run the tests, as well.
+    #[must_use]
+    pub fn test(&self) -> bool {
+        self.test.unwrap_or_default()
+    }
+}
+
+impl SemverConfig {
+    /// The Git reference or the `.crate` file to compare to.
+    #[must_use]
+    pub fn baseline(&self) -> &str {
+        &self.baseline
+    }
+}
+
+impl UnusedConfig {
+    /// The dependencies to never report.
+    #[must_use]
+    pub fn allow(&self) -> &[String] {
+        &self.allow
+    }
+
+    /// Whether unused dependencies let the detection fail.
+    #[must_use]
+    pub const fn deny(&self) -> bool {
+        self.deny
+    }
+
+    /// Whether the compiler shall be asked for the unused dependencies.
+    #[must_use]
+    pub const fn precise(&self) -> bool {
+        self.precise
+    }
+}
+
+/******************************************************************************/
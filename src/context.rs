@@ -0,0 +1,59 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The context a step is run in.
+///
+/// The context is shared by all steps of a run of an `Application`.
+#[derive(Clone)]
+pub struct Context {
+    /// The verbosity of the run.
+    verbosity: crate::Verbosity,
+
+    /// The directory containing the workspace manifest, if known.
+    workspace_root: Option<std::path::PathBuf>,
+}
+
+impl Context {
+    /// Create a new context.
+    #[must_use]
+    pub const fn new(
+        verbosity: crate::Verbosity,
+        workspace_root: Option<std::path::PathBuf>,
+    ) -> Self {
+        Self {
+            verbosity,
+            workspace_root,
+        }
+    }
+
+    /// The verbosity of the run.
+    #[must_use]
+    pub const fn verbosity(&self) -> crate::Verbosity {
+        self.verbosity
+    }
+
+    /// The directory containing the workspace manifest, if known.
+    #[must_use]
+    pub fn workspace_root(&self) -> Option<&std::path::Path> {
+        self.workspace_root.as_deref()
+    }
+}
+
+/******************************************************************************/
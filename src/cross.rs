@@ -0,0 +1,127 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A check of the workspace for another target triple.
+///
+/// The workspace is checked by `cargo check` and Clippy with `--target`.  This
+/// requires the standard library of the target to be installed by `rustup`.
+/// If it is not, the check is skipped instead of failing with the confusing
+/// errors Cargo reports in this case.
+#[derive(Clone)]
+pub struct Cross {
+    /// The arguments to pass to Clippy, such as the lints to deny.
+    lints: Vec<String>,
+
+    /// The target triple to check for.
+    target: String,
+}
+
+impl Cross {
+    /// Run the given Cargo subcommand for the target.
+    ///
+    /// On failure, the error is returned, see `Process::verify`.
+    fn cargo(&self, subcommand: &str, lints: &[String]) -> Result<(), String> {
+        let mut arguments = vec![subcommand.into(), "--target".into(), self.target.clone()];
+
+        if !lints.is_empty() {
+            arguments.push("--".into());
+            arguments.extend_from_slice(lints);
+        }
+
+        crate::Process::new("cargo", arguments, crate::Verbosity::Silent).verify("cargo failed")
+    }
+
+    /// Query the target triples whose standard library is installed by
+    /// `rustup` for the active toolchain.
+    ///
+    /// If `rustup` should not be available, no targets will be returned.
+    #[must_use]
+    pub fn installed() -> Vec<String> {
+        let mut rustup = crate::Process::new(
+            "rustup",
+            vec!["target".into(), "list".into(), "--installed".into()],
+            crate::Verbosity::Silent,
+        );
+
+        if rustup.run().is_failure() || !rustup.success() {
+            return Vec::new();
+        }
+
+        rustup
+            .stdout()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Configure a new check for the given target triple.
+    ///
+    /// The given arguments are passed to Clippy, such as the lints to deny.
+    #[must_use]
+    pub fn new(target: &str, lints: Vec<String>) -> Self {
+        Self {
+            lints,
+            target: target.into(),
+        }
+    }
+
+    /// Check the workspace for the target.
+    ///
+    /// Clippy is only run if `cargo check` succeeds since it would report the
+    /// same errors, otherwise.  If the target is not installed, the check is
+    /// skipped.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+
+        if !Self::installed().contains(&self.target) {
+            findings.skip(format!("target {} not installed", self.target));
+            return findings;
+        }
+
+        if let Err(error) = self.cargo("check", &[]) {
+            findings.error(format!("check:  {error}"));
+        } else if let Err(error) = self.cargo("clippy", &self.lints) {
+            findings.error(format!("clippy:  {error}"));
+        }
+
+        findings
+    }
+}
+
+impl std::fmt::Display for Cross {
+    /// Implements the `Display` trait.
+    ///
+    /// A check will be formatted like a command line naming the target and the
+    /// arguments passed to Clippy.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cross --target {}", self.target)?;
+
+        if self.lints.is_empty() {
+            Ok(())
+        } else {
+            write!(f, " -- {}", self.lints.join(" "))
+        }
+    }
+}
+
+/******************************************************************************/
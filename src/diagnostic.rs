@@ -0,0 +1,141 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A diagnostic message reported by the compiler or by one of its tools.
+///
+/// Diagnostics are read from the JSON messages Cargo emits when called with
+/// `--message-format=json`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file the primary span of the diagnostic is located in, if any.
+    file: Option<String>,
+
+    /// The severity of the diagnostic, such as `warning` or `error`.
+    level: String,
+
+    /// The line the primary span of the diagnostic starts at, if any.
+    line: Option<u64>,
+
+    /// The lint or error code the diagnostic originates from, if any.
+    lint: Option<String>,
+
+    /// The message of the diagnostic.
+    message: String,
+
+    /// The diagnostic as it would have been written to `stderr`.
+    rendered: String,
+}
+
+impl Diagnostic {
+    /// The file the primary span of the diagnostic is located in, if any.
+    #[must_use]
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Read a single diagnostic from the given compiler message.
+    ///
+    /// Messages which neither point to a location nor name a lint, such as the
+    /// number of emitted warnings, are not considered diagnostics.
+    fn from_message(message: &serde_json::Value) -> Option<Self> {
+        let text = |value: &serde_json::Value| value.as_str().map(String::from);
+        let lint = message.pointer("/code/code").and_then(text);
+        let primary = message
+            .get("spans")
+            .and_then(serde_json::Value::as_array)
+            .and_then(|spans| {
+                spans.iter().find(|span| {
+                    span.get("is_primary")
+                        .and_then(serde_json::Value::as_bool)
+                        .unwrap_or_default()
+                })
+            });
+
+        if lint.is_none() && primary.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            file: primary
+                .and_then(|span| span.get("file_name"))
+                .and_then(text),
+            level: message.get("level").and_then(text)?,
+            line: primary
+                .and_then(|span| span.get("line_start"))
+                .and_then(serde_json::Value::as_u64),
+            lint,
+            message: message.get("message").and_then(text)?,
+            rendered: message.get("rendered").and_then(text).unwrap_or_default(),
+        })
+    }
+
+    /// The line the primary span of the diagnostic starts at, if any.
+    #[must_use]
+    pub const fn line(&self) -> Option<u64> {
+        self.line
+    }
+
+    /// The lint or error code the diagnostic originates from, if any.
+    #[must_use]
+    pub fn lint(&self) -> Option<&str> {
+        self.lint.as_deref()
+    }
+
+    /// The message of the diagnostic.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Read all diagnostics from the given output of Cargo.
+    ///
+    /// Lines which are no compiler messages are skipped.  Since Cargo reports
+    /// the same diagnostic once per affected target, duplicates are removed.
+    #[must_use]
+    pub fn parse(output: &str) -> Vec<Self> {
+        let mut diagnostics = Vec::new();
+
+        for line in output.lines() {
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+
+            if value.get("reason").and_then(serde_json::Value::as_str) != Some("compiler-message") {
+                continue;
+            }
+
+            if let Some(diagnostic) = value.get("message").and_then(Self::from_message) {
+                if !diagnostics.contains(&diagnostic) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    /// The diagnostic as it would have been written to `stderr`.
+    #[must_use]
+    pub fn rendered(&self) -> &str {
+        &self.rendered
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,213 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A report of the packages resolved in multiple incompatible versions.
+///
+/// Versions are considered incompatible according to the rules of Cargo, this
+/// is, if they differ in their left-most non-zero component.  Each duplicate
+/// adds to the build times since all of its versions need to be compiled.
+/// Only the packages built for the host platform are considered such that
+/// duplicates within the dependencies of other platforms are not reported.
+#[derive(Clone)]
+pub struct Duplicates {
+    /// The names of the packages whose duplicates are never reported.
+    allow: Vec<String>,
+
+    /// The names of the packages which must not be duplicated.
+    deny: Vec<String>,
+
+    /// The number of duplicated packages to tolerate, if limited.
+    max: Option<usize>,
+
+    /// The information about the workspace to check.
+    metadata: std::sync::Arc<crate::Metadata>,
+}
+
+impl Duplicates {
+    /// The key of the versions compatible to the given one.
+    ///
+    /// Versions are compatible according to the rules of Cargo if they agree
+    /// in their left-most non-zero component.
+    #[must_use]
+    pub fn compatibility(version: &semver::Version) -> String {
+        match (version.major, version.minor) {
+            (0, 0) => format!("0.0.{}", version.patch),
+            (0, minor) => format!("0.{minor}"),
+            (major, _) => major.to_string(),
+        }
+    }
+
+    /// Configure a new report of the duplicated packages.
+    #[must_use]
+    pub fn new(
+        config: &crate::DuplicatesConfig,
+        metadata: std::sync::Arc<crate::Metadata>,
+    ) -> Self {
+        Self {
+            allow: config.allow().to_vec(),
+            deny: config.deny().to_vec(),
+            max: config.max(),
+            metadata,
+        }
+    }
+
+    /// Report the duplicated packages together with their dependents.
+    ///
+    /// Duplicates are only reported as warnings unless the package must not be
+    /// duplicated or the number of duplicated packages exceeds the configured
+    /// maximum.  If the dependencies cannot be restricted to the host
+    /// platform, those of all platforms are considered and a warning is
+    /// reported.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let host = self.metadata.host();
+
+        if host.is_none() {
+            findings.warning("failed to restrict the packages to the host platform".into());
+        }
+
+        let metadata = host.as_ref().unwrap_or(&self.metadata);
+        let mut packages = std::collections::BTreeMap::<
+            &str,
+            std::collections::BTreeMap<String, Vec<(semver::Version, &crate::Package)>>,
+        >::new();
+
+        for package in metadata.packages() {
+            if let Ok(version) = semver::Version::parse(package.version()) {
+                packages
+                    .entry(package.name())
+                    .or_default()
+                    .entry(Self::compatibility(&version))
+                    .or_default()
+                    .push((version, package));
+            }
+        }
+
+        let duplicates = packages
+            .into_iter()
+            .filter(|(name, versions)| {
+                versions.len() > 1 && !self.allow.iter().any(|allowed| allowed == name)
+            })
+            .collect::<Vec<_>>();
+        let exceeded = self.max.is_some_and(|max| duplicates.len() > max);
+
+        if exceeded {
+            findings.error(format!(
+                "{} packages are duplicated, exceeding the maximum of {}",
+                duplicates.len(),
+                self.max.unwrap_or_default()
+            ));
+        }
+
+        for (name, versions) in duplicates {
+            let mut versions = versions
+                .into_values()
+                .flatten()
+                .collect::<Vec<(semver::Version, &crate::Package)>>();
+            versions.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            let finding = format!(
+                "{name}:  {}",
+                versions
+                    .iter()
+                    .map(|(version, package)| {
+                        let dependents = metadata
+                            .dependents(package.id())
+                            .iter()
+                            .map(|dependent| {
+                                format!("{} {}", dependent.name(), dependent.version())
+                            })
+                            .collect::<Vec<String>>();
+
+                        if dependents.is_empty() {
+                            version.to_string()
+                        } else {
+                            format!("{version} (via {})", dependents.join(", "))
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+
+            if exceeded || self.deny.iter().any(|denied| denied == name) {
+                findings.error(finding);
+            } else {
+                findings.warning(finding);
+            }
+        }
+
+        findings
+    }
+}
+
+impl std::fmt::Display for Duplicates {
+    /// Implements the `Display` trait.
+    ///
+    /// A report will be formatted like a command line naming the allowed and
+    /// denied duplicates as well as the maximum.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "duplicates")?;
+
+        if let Some(max) = self.max {
+            write!(f, " --max {max}")?;
+        }
+
+        for name in &self.allow {
+            write!(f, " --allow {name}")?;
+        }
+
+        for name in &self.deny {
+            write!(f, " --deny {name}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn compatibility(version: &str) -> String {
+        super::Duplicates::compatibility(&semver::Version::parse(version).unwrap())
+    }
+
+    #[test]
+    fn compatibility_major() {
+        assert_eq!(compatibility("1.2.3"), "1");
+        assert_eq!(compatibility("1.9.0"), compatibility("1.0.0"));
+        assert_ne!(compatibility("2.0.0"), compatibility("1.0.0"));
+    }
+
+    #[test]
+    fn compatibility_minor() {
+        assert_eq!(compatibility("0.3.1"), "0.3");
+        assert_eq!(compatibility("0.3.9"), compatibility("0.3.0"));
+        assert_ne!(compatibility("0.4.0"), compatibility("0.3.0"));
+    }
+
+    #[test]
+    fn compatibility_patch() {
+        assert_eq!(compatibility("0.0.1"), "0.0.1");
+        assert_ne!(compatibility("0.0.2"), compatibility("0.0.1"));
+        assert_ne!(compatibility("0.0.1"), compatibility("0.1.0"));
+    }
+}
+
+/******************************************************************************/
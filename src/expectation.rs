@@ -0,0 +1,175 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The expectations a process needs to meet in order to succeed.
+///
+/// By default, a process succeeds if and only if it exits with code zero.
+/// Further exit codes can be accepted.  In addition, the output written to
+/// `stdout` can be required to match a pattern while the output written to
+/// `stderr` can be required not to match another one.  At least one exit code
+/// needs to be accepted.
+#[derive(Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Expectation {
+    /// The accepted exit codes.
+    #[serde(deserialize_with = "Expectation::exit_codes")]
+    exit_codes: Vec<i32>,
+
+    /// The pattern `stderr` must not match, if any.
+    #[serde(deserialize_with = "Expectation::pattern")]
+    failure: Option<regex::Regex>,
+
+    /// The pattern `stdout` must match, if any.
+    #[serde(deserialize_with = "Expectation::pattern")]
+    success: Option<regex::Regex>,
+}
+
+impl Expectation {
+    /// Deserialise a non-empty list of exit codes.
+    fn exit_codes<'de, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let exit_codes = <Vec<i32> as serde::Deserialize>::deserialize(deserializer)?;
+
+        if exit_codes.is_empty() {
+            Err(serde::de::Error::invalid_length(
+                0,
+                &"at least one exit code",
+            ))
+        } else {
+            Ok(exit_codes)
+        }
+    }
+
+    /// Whether a process meets these expectations.
+    #[must_use]
+    pub fn holds(&self, exit: i32, stdout: &str, stderr: &str) -> bool {
+        self.exit_codes.contains(&exit)
+            && self
+                .success
+                .as_ref()
+                .map_or(true, |pattern| pattern.is_match(stdout))
+            && !self
+                .failure
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(stderr))
+    }
+
+    /// Deserialise an optional regular expression.
+    fn pattern<'de, D>(deserializer: D) -> Result<Option<regex::Regex>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <Option<String> as serde::Deserialize>::deserialize(deserializer)?
+            .map(|pattern| regex::Regex::new(&pattern).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+impl Default for Expectation {
+    /// Only the exit code zero is accepted by default.
+    fn default() -> Self {
+        Self {
+            exit_codes: vec![0],
+            failure: None,
+            success: None,
+        }
+    }
+}
+
+impl std::fmt::Display for Expectation {
+    /// Implements the `Display` trait.
+    ///
+    /// An expectation will be formatted as an enumeration of the accepted exit
+    /// codes and the patterns the output needs to match or not to match.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expecting exit code {}",
+            self.exit_codes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(" or ")
+        )?;
+
+        if let Some(pattern) = &self.success {
+            write!(f, ", stdout matching '{pattern}'")?;
+        }
+
+        if let Some(pattern) = &self.failure {
+            write!(f, ", stderr not matching '{pattern}'")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Parse the given expectation in the notation of the configuration.
+    fn parse(expectation: &str) -> Result<super::Expectation, toml::de::Error> {
+        toml::from_str(expectation)
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert!(parse("exit_codes = []").is_err());
+        assert!(parse("exit_codes = [1]").is_ok());
+        assert_eq!(
+            parse("exit_codes = [0, 1]").unwrap().to_string(),
+            "expecting exit code 0 or 1"
+        );
+    }
+
+    #[test]
+    fn holds() {
+        let expectation =
+            parse("exit_codes = [0, 2]\nsuccess = '(?m)^ok$'\nfailure = '(?i)error'").unwrap();
+
+        assert!(expectation.holds(0, "ok\n", ""));
+        assert!(expectation.holds(2, "ok", "warning"));
+        assert!(!expectation.holds(1, "ok", ""));
+        assert!(!expectation.holds(0, "not ok", ""));
+        assert!(!expectation.holds(0, "ok", "ERROR:  failed"));
+    }
+
+    #[test]
+    fn holds_default() {
+        let expectation = parse("").unwrap();
+
+        assert!(expectation.holds(0, "anything", "error"));
+        assert!(!expectation.holds(1, "", ""));
+        assert_eq!(expectation.to_string(), "expecting exit code 0");
+    }
+
+    #[test]
+    fn pattern() {
+        assert!(parse("success = '('").is_err());
+        assert!(parse("failure = 1").is_err());
+        assert_eq!(
+            parse("success = 'a+'\nfailure = 'b'").unwrap().to_string(),
+            "expecting exit code 0, stdout matching 'a+', stderr not matching 'b'"
+        );
+    }
+}
+
+/******************************************************************************/
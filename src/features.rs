@@ -0,0 +1,277 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A check of the combinations of the features of the members.
+///
+/// Besides the default features, which are covered by the regular steps, each
+/// member is checked without its default features, with all of its features
+/// and with each feature on its own.  If a depth is configured, all
+/// combinations of up to that many features are checked instead of the single
+/// features.  Excluded features are never enabled.  The combinations share a
+/// dedicated target directory such that the regular build artifacts are not
+/// invalidated.
+#[derive(Clone)]
+pub struct Features {
+    /// Whether to run Clippy instead of `cargo check` for each combination.
+    clippy: bool,
+
+    /// The maximum number of features to combine.
+    depth: usize,
+
+    /// The names of the features which are never enabled.
+    exclude: Vec<String>,
+
+    /// The information about the workspace to check.
+    metadata: std::sync::Arc<crate::Metadata>,
+}
+
+impl Features {
+    /// Check the given package with the given feature arguments.
+    ///
+    /// On failure, the error is returned, see `Process::verify`.
+    fn check(&self, package: &str, features: &[String]) -> Result<(), String> {
+        let target = self
+            .metadata
+            .workspace()
+            .target()
+            .join("optimise")
+            .join("features");
+        let mut arguments = vec![
+            if self.clippy { "clippy" } else { "check" }.into(),
+            "-p".into(),
+            package.into(),
+            "--target-dir".into(),
+            target.to_string_lossy().into(),
+        ];
+        arguments.extend_from_slice(features);
+
+        if self.clippy {
+            arguments.push("--".into());
+            arguments.push("-D".into());
+            arguments.push("warnings".into());
+        }
+
+        crate::Process::new("cargo", arguments, crate::Verbosity::Silent).verify("cargo failed")
+    }
+
+    /// The feature arguments to check the given package with.
+    ///
+    /// The default features are not considered since they are covered by the
+    /// regular steps.
+    fn combinations(&self, package: &crate::Package) -> Vec<Vec<String>> {
+        let features = package
+            .features()
+            .keys()
+            .filter(|feature| *feature != "default" && !self.exclude.contains(feature))
+            .map(String::as_str)
+            .collect::<Vec<&str>>();
+        let mut subsets = vec![Vec::<&str>::new()];
+
+        for feature in &features {
+            for index in 0..subsets.len() {
+                if subsets[index].len() < self.depth {
+                    let mut subset = subsets[index].clone();
+                    subset.push(feature);
+                    subsets.push(subset);
+                }
+            }
+        }
+
+        subsets.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+        let mut combinations = vec![vec!["--no-default-features".into()]];
+
+        if self.exclude.is_empty() {
+            combinations.push(vec!["--all-features".into()]);
+        } else if !features.is_empty() {
+            combinations.push(vec![
+                "--no-default-features".into(),
+                "--features".into(),
+                features.join(","),
+            ]);
+        }
+
+        for subset in subsets.into_iter().filter(|subset| !subset.is_empty()) {
+            let combination = vec![
+                "--no-default-features".into(),
+                "--features".into(),
+                subset.join(","),
+            ];
+
+            if !combinations.contains(&combination) {
+                combinations.push(combination);
+            }
+        }
+
+        combinations
+    }
+
+    /// Configure a new check of the feature combinations.
+    #[must_use]
+    pub fn new(config: &crate::FeaturesConfig, metadata: std::sync::Arc<crate::Metadata>) -> Self {
+        Self {
+            clippy: config.clippy(),
+            depth: config.depth().unwrap_or(1).max(1),
+            exclude: config.exclude().to_vec(),
+            metadata,
+        }
+    }
+
+    /// Check the feature combinations of all members declaring features.
+    ///
+    /// Each failing combination is reported together with the first error of
+    /// Cargo.  If no member declares any features, the check is skipped.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let mut checked = false;
+
+        for package in self.metadata.members() {
+            if package.features().is_empty() {
+                continue;
+            }
+
+            for combination in self.combinations(package) {
+                checked = true;
+
+                if let Err(error) = self.check(package.name(), &combination) {
+                    findings.error(format!(
+                        "{} {}:  {error}",
+                        package.name(),
+                        combination.join(" ")
+                    ));
+                }
+            }
+        }
+
+        if !checked {
+            findings.skip("feature check skipped:  no member declares features".into());
+        }
+
+        findings
+    }
+}
+
+impl std::fmt::Display for Features {
+    /// Implements the `Display` trait.
+    ///
+    /// A check will be formatted like a command line naming its settings.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "features --depth {}", self.depth)?;
+
+        if self.clippy {
+            write!(f, " --clippy")?;
+        }
+
+        for feature in &self.exclude {
+            write!(f, " --exclude {feature}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// The feature arguments of the combinations of a package with the
+    /// features `a`, `b` and `c`, the latter enabled by default.
+    fn combinations(depth: usize, exclude: &[&str]) -> Vec<String> {
+        let metadata = serde_json::from_value::<crate::Metadata>(serde_json::json!({
+            "packages": [{
+                "dependencies": [],
+                "features": {"a": [], "b": ["a"], "c": [], "default": ["c"]},
+                "id": "member 0.1.0",
+                "license": null,
+                "license_file": null,
+                "manifest_path": "Cargo.toml",
+                "name": "member",
+                "rust_version": null,
+                "source": null,
+                "targets": [],
+                "version": "0.1.0"
+            }],
+            "resolve": null,
+            "target_directory": "target",
+            "workspace_members": ["member 0.1.0"],
+            "workspace_root": "."
+        }))
+        .unwrap();
+        let features = super::Features {
+            clippy: false,
+            depth,
+            exclude: exclude.iter().map(ToString::to_string).collect(),
+            metadata: std::sync::Arc::new(metadata.clone()),
+        };
+
+        features
+            .combinations(&metadata.packages()[0])
+            .iter()
+            .map(|combination| combination.join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn combinations_exclude() {
+        assert_eq!(
+            combinations(1, &["b"]),
+            [
+                "--no-default-features",
+                "--no-default-features --features a,c",
+                "--no-default-features --features a",
+                "--no-default-features --features c",
+            ]
+        );
+        assert_eq!(combinations(2, &["a", "b", "c"]), ["--no-default-features"]);
+    }
+
+    #[test]
+    fn combinations_powerset() {
+        assert_eq!(
+            combinations(2, &[]),
+            [
+                "--no-default-features",
+                "--all-features",
+                "--no-default-features --features a",
+                "--no-default-features --features b",
+                "--no-default-features --features c",
+                "--no-default-features --features a,b",
+                "--no-default-features --features a,c",
+                "--no-default-features --features b,c",
+            ]
+        );
+        assert_eq!(combinations(5, &[]).len(), 9);
+    }
+
+    #[test]
+    fn combinations_single() {
+        assert_eq!(
+            combinations(1, &[]),
+            [
+                "--no-default-features",
+                "--all-features",
+                "--no-default-features --features a",
+                "--no-default-features --features b",
+                "--no-default-features --features c",
+            ]
+        );
+    }
+}
+
+/******************************************************************************/
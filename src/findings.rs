@@ -0,0 +1,79 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The findings of a check run in-process.
+///
+/// Errors let the step fail while warnings are only reported.  Both are shown
+/// in the summaries of the run.  If a check cannot be run, for instance due to
+/// a missing toolchain, it is skipped instead.
+#[derive(Default)]
+pub struct Findings {
+    /// The findings letting the step fail.
+    errors: Vec<String>,
+
+    /// The reason why the check could not be run, if so.
+    skipped: Option<String>,
+
+    /// The findings to be reported without failing.
+    warnings: Vec<String>,
+}
+
+impl Findings {
+    /// Record a finding which lets the step fail.
+    pub fn error(&mut self, finding: String) {
+        self.errors.push(finding);
+    }
+
+    /// The findings letting the step fail.
+    #[must_use]
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Whether at least one finding lets the step fail.
+    #[must_use]
+    pub fn is_failure(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Record that the check could not be run for the given reason.
+    pub fn skip(&mut self, reason: String) {
+        self.skipped = Some(reason);
+    }
+
+    /// The reason why the check could not be run, if so.
+    #[must_use]
+    pub fn skipped(&self) -> Option<&str> {
+        self.skipped.as_deref()
+    }
+
+    /// Record a finding which is reported without failing.
+    pub fn warning(&mut self, finding: String) {
+        self.warnings.push(finding);
+    }
+
+    /// The findings to be reported without failing.
+    #[must_use]
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,505 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A check of the license headers at the top of the source files.
+///
+/// The header is given as a template which may contain the placeholders
+/// `{year}` and `{author}`.  A header whose lines match the template with
+/// arbitrary replacements for the placeholders, but not with the configured
+/// ones, is considered outdated.  Otherwise, a file not starting with the
+/// header is considered to miss it.  A shebang line is kept above the header.
+///
+/// Templates with the comment style `verbatim` already contain the comment
+/// syntax, such as the boxed banners of this project.  When a placeholder is
+/// replaced in a line ending with a border, the spaces before the border are
+/// adjusted such that the line has the width of the widest template line.
+/// Runs of whitespace are insignificant when detecting outdated headers.
+#[derive(Clone)]
+pub struct Headers {
+    /// The author to insert for the placeholder `{author}`.
+    author: String,
+
+    /// The extensions of the files to check.
+    extensions: Vec<String>,
+
+    /// Whether missing and outdated headers shall be fixed.
+    fix: bool,
+
+    /// The comment styles overriding the defaults per extension.
+    styles: std::collections::BTreeMap<String, crate::CommentStyle>,
+
+    /// The template of the header, unless given per extension.
+    template: String,
+
+    /// The templates overriding the default one per extension.
+    templates: std::collections::BTreeMap<String, String>,
+
+    /// The workspace to check.
+    workspace: crate::Workspace,
+
+    /// The year to insert for the placeholder `{year}`.
+    year: String,
+}
+
+/// The state of the header of a single file.
+enum State {
+    /// The file starts with the expected header.
+    Current,
+
+    /// The file does not start with a header.
+    Missing,
+
+    /// The file starts with a header for another year or author.
+    Outdated,
+}
+
+impl Headers {
+    /// Adjust the spaces before the border of a substituted template line.
+    ///
+    /// A line ends with a border if its last word consists of punctuation
+    /// only.  The substituted line is
+    /// padded or shrunk to the given width, keeping at least one space
+    /// before the border.
+    fn align(line: &str, width: usize) -> String {
+        let Some((body, border)) = line.trim_end().rsplit_once(' ') else {
+            return line.into();
+        };
+
+        if border.is_empty()
+            || !border
+                .chars()
+                .all(|character| character.is_ascii_punctuation())
+        {
+            return line.into();
+        }
+
+        let body = body.trim_end();
+        let used = body.chars().count() + border.chars().count();
+
+        format!(
+            "{body}{}{border}",
+            " ".repeat(width.saturating_sub(used).max(1))
+        )
+    }
+
+    /// Replace the header at the given line of the contents.
+    ///
+    /// The given number of lines following the preceding ones are replaced
+    /// by the header.  An inserted header is separated from the following
+    /// code by an empty line.  The line endings of the file as well as the
+    /// remaining lines are kept as they are.
+    fn fix(contents: &str, skip: usize, replaced: usize, header: &[String]) -> String {
+        let newline = if contents.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let segments = contents.split_inclusive('\n').collect::<Vec<&str>>();
+        let skip = skip.min(segments.len());
+        let mut result = segments[..skip].concat();
+        let rest = segments[(skip + replaced).min(segments.len())..].concat();
+
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push_str(newline);
+        }
+
+        for line in header {
+            result.push_str(line);
+            result.push_str(newline);
+        }
+
+        if replaced == 0 && !rest.is_empty() && !rest.starts_with(['\r', '\n']) {
+            result.push_str(newline);
+        }
+
+        result + &rest
+    }
+
+    /// Check the header of the given contents.
+    ///
+    /// The expected lines are compared as they are while the patterns may
+    /// match any replacements of the placeholders.  The number of lines
+    /// preceding the header, such as a shebang, is returned together with the
+    /// state.
+    fn inspect(contents: &str, expected: &[String], patterns: &[String]) -> (usize, State) {
+        let skip = usize::from(contents.starts_with("#!") && !contents.starts_with("#!["));
+        let lines = contents.lines().skip(skip).collect::<Vec<&str>>();
+
+        if lines.len() >= expected.len()
+            && lines
+                .iter()
+                .zip(expected)
+                .all(|(line, expected)| line == expected)
+        {
+            return (skip, State::Current);
+        }
+
+        if lines.len() >= patterns.len()
+            && lines
+                .iter()
+                .zip(patterns)
+                .all(|(line, pattern)| Self::matches(line, pattern))
+        {
+            (skip, State::Outdated)
+        } else {
+            (skip, State::Missing)
+        }
+    }
+
+    /// Whether the given line matches the given pattern.
+    ///
+    /// The placeholders of the pattern match arbitrary text.  Runs of
+    /// whitespace are compared as single spaces.
+    fn matches(line: &str, pattern: &str) -> bool {
+        let line = line.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let pattern = pattern
+            .replace("{author}", "{year}")
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let segments = pattern.split("{year}").collect::<Vec<&str>>();
+        let [first, middle @ .., last] = segments.as_slice() else {
+            return line == pattern;
+        };
+
+        let Some(mut rest) = line.strip_prefix(first) else {
+            return false;
+        };
+
+        for segment in middle {
+            match rest.find(segment) {
+                Some(index) => rest = &rest[index + segment.len()..],
+                None => return false,
+            }
+        }
+
+        rest.ends_with(last)
+    }
+
+    /// Configure a new check of the license headers.
+    ///
+    /// If no year is configured, the current one will be used.
+    #[must_use]
+    pub fn new(config: &crate::HeadersConfig, fix: bool, workspace: &crate::Workspace) -> Self {
+        let year = config.year().map_or_else(
+            || {
+                crate::date(
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs(),
+                )[..4]
+                    .to_string()
+            },
+            Into::into,
+        );
+
+        Self {
+            author: config.author().into(),
+            extensions: config.extensions().to_vec(),
+            fix,
+            styles: config.styles().clone(),
+            template: config.template().into(),
+            templates: config.templates().clone(),
+            workspace: workspace.clone(),
+            year,
+        }
+    }
+
+    /// Whether missing and outdated headers are fixed.
+    #[must_use]
+    pub const fn is_mutating(&self) -> bool {
+        self.fix
+    }
+
+    /// Check the headers of all files with one of the configured extensions.
+    ///
+    /// In fix mode, missing headers are inserted and outdated ones are
+    /// replaced.  The fixed files are reported as warnings.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+
+        for file in self.workspace.files() {
+            let Some(extension) = file
+                .extension()
+                .and_then(std::ffi::OsStr::to_str)
+                .filter(|extension| self.extensions.iter().any(|wanted| wanted == extension))
+            else {
+                continue;
+            };
+            let Some(style) = self
+                .styles
+                .get(extension)
+                .copied()
+                .or_else(|| crate::CommentStyle::for_extension(extension))
+            else {
+                findings.error(format!("no comment style known for `.{extension}` files"));
+                continue;
+            };
+            let Some(template) = self.template(extension) else {
+                findings.error(format!("no template known for `.{extension}` files"));
+                continue;
+            };
+            let Ok(contents) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            let path = file
+                .strip_prefix(self.workspace.root())
+                .unwrap_or(&file)
+                .display()
+                .to_string();
+            let substituted = self.substitute(template);
+            let expected = style.wrap(&substituted.iter().map(String::as_str).collect::<Vec<_>>());
+            let patterns = style.wrap(&template.lines().collect::<Vec<&str>>());
+            let (skip, state) = Self::inspect(&contents, &expected, &patterns);
+            let (replaced, state) = match state {
+                State::Current => continue,
+                State::Missing => (0, "missing"),
+                State::Outdated => (expected.len(), "outdated"),
+            };
+
+            if !self.fix {
+                findings.error(format!("{path}:  {state} license header"));
+                continue;
+            }
+
+            let contents = Self::fix(&contents, skip, replaced, &expected);
+
+            if std::fs::write(&file, contents).is_ok() {
+                findings.warning(format!("{path}:  fixed {state} license header"));
+            } else {
+                findings.error(format!("{path}:  failed to fix the {state} license header"));
+            }
+        }
+
+        findings
+    }
+
+    /// Replace the placeholders of the given template.
+    fn substitute(&self, template: &str) -> Vec<String> {
+        let width = template
+            .lines()
+            .map(|line| line.trim_end().chars().count())
+            .max()
+            .unwrap_or_default();
+
+        template
+            .lines()
+            .map(|line| {
+                let substituted = line
+                    .replace("{author}", &self.author)
+                    .replace("{year}", &self.year);
+
+                if substituted == line {
+                    substituted
+                } else {
+                    Self::align(&substituted, width)
+                }
+            })
+            .collect()
+    }
+
+    /// The template for the files with the given extension.
+    fn template(&self, extension: &str) -> Option<&str> {
+        self.templates
+            .get(extension)
+            .map(String::as_str)
+            .or_else(|| Some(self.template.as_str()).filter(|template| !template.is_empty()))
+    }
+}
+
+impl std::fmt::Display for Headers {
+    /// Implements the `Display` trait.
+    ///
+    /// A check will be formatted like a command line naming the checked
+    /// extensions together with their comment styles.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "headers --year {} --author '{}'", self.year, self.author)?;
+
+        for extension in &self.extensions {
+            write!(f, " --extension {extension}")?;
+
+            if let Some(style) = self.styles.get(extension) {
+                write!(f, "='{style}'")?;
+            }
+        }
+
+        if self.fix {
+            write!(f, " --fix")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// A boxed template whose line with the placeholders is not aligned.
+    const BOXED: &str = r"/******* License *******\
+|                       |
+|  (C) {year} {author} |
+\***********************/
+";
+
+    /// Configure a fixing check of a fresh temporary workspace.
+    fn headers(name: &str, config: &str) -> super::Headers {
+        let directory =
+            std::env::temp_dir().join(format!("optimise-headers-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("root")).unwrap();
+        let workspace = crate::Workspace::new(&directory.join("root"), &directory.join("target"));
+        let config = toml::from_str::<crate::HeadersConfig>(config).unwrap();
+        super::Headers::new(&config, true, &workspace)
+    }
+
+    #[test]
+    fn align() {
+        assert_eq!(
+            super::Headers::align("|  (C) 2022 Me  |", 25),
+            "|  (C) 2022 Me          |"
+        );
+        assert_eq!(
+            super::Headers::align("|  (C) 2022 Kevin Matthes  |", 25),
+            "|  (C) 2022 Kevin Matthes |"
+        );
+        assert_eq!(
+            super::Headers::align("// (C) 2022 Me", 25),
+            "// (C) 2022 Me"
+        );
+    }
+
+    #[test]
+    fn fix() {
+        let header = ["// A".to_string(), "// B".to_string()];
+
+        assert_eq!(
+            super::Headers::fix("fn f() {}  \r\n", 0, 0, &header),
+            "// A\r\n// B\r\n\r\nfn f() {}  \r\n"
+        );
+        assert_eq!(
+            super::Headers::fix("#!/bin/sh\n# Old\n\necho  \n", 1, 1, &header[..1]),
+            "#!/bin/sh\n// A\n\necho  \n"
+        );
+        assert_eq!(
+            super::Headers::fix("#!/bin/sh", 1, 0, &header[..1]),
+            "#!/bin/sh\n// A\n"
+        );
+        assert_eq!(super::Headers::fix("", 0, 0, &header), "// A\n// B\n");
+    }
+
+    #[test]
+    fn inspect() {
+        let expected = ["// (C) 2022 Me".to_string()];
+        let patterns = ["// (C) {year} {author}".to_string()];
+        let state = |contents| super::Headers::inspect(contents, &expected, &patterns);
+
+        assert!(matches!(
+            state("// (C) 2022 Me\n"),
+            (0, super::State::Current)
+        ));
+        assert!(matches!(
+            state("#!/bin/sh\n// (C) 2022 Me\n"),
+            (1, super::State::Current)
+        ));
+        assert!(matches!(
+            state("// (C) 2021 You\r\n"),
+            (0, super::State::Outdated)
+        ));
+        assert!(matches!(
+            state("#![deny(warnings)]\n"),
+            (0, super::State::Missing)
+        ));
+        assert!(matches!(state(""), (0, super::State::Missing)));
+    }
+
+    #[test]
+    fn matches() {
+        assert!(super::Headers::matches(
+            "(C) 2022 Me",
+            "(C) {year} {author}"
+        ));
+        assert!(super::Headers::matches(
+            "|  (C) 2022 Me    |",
+            "|  (C) {year} {author}  |"
+        ));
+        assert!(super::Headers::matches(
+            "no placeholders",
+            "no  placeholders"
+        ));
+        assert!(!super::Headers::matches(
+            "(C) 2022 Me",
+            "(c) {year} {author}"
+        ));
+        assert!(!super::Headers::matches("(C) 2022", "(C) {year} {author}"));
+        assert!(!super::Headers::matches("other", "no placeholders"));
+    }
+
+    #[test]
+    fn run_verbatim() {
+        let headers = headers(
+            "verbatim",
+            &format!("author = 'Me'\nyear = '2022'\ntemplate = '''\n{BOXED}'''\nstyles.rs = 'verbatim'\n"),
+        );
+        let root = headers.workspace.root().to_path_buf();
+        let header = BOXED
+            .replace("{year} {author} |", "2022 Me          |")
+            .replace('\n', "\r\n");
+        let outdated = header.replace("2022 Me        ", "2021 Someone Else");
+        std::fs::write(
+            root.join("current.rs"),
+            format!("{header}\r\nfn f() {{}}\r\n"),
+        )
+        .unwrap();
+        std::fs::write(root.join("missing.rs"), "fn f() {}  \r\n").unwrap();
+        std::fs::write(
+            root.join("outdated.rs"),
+            format!("{outdated}\r\nfn f() {{}}\r\n"),
+        )
+        .unwrap();
+
+        let findings = headers.run();
+
+        assert!(findings.errors().is_empty());
+        assert_eq!(findings.warnings().len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(root.join("missing.rs")).unwrap(),
+            format!("{header}\r\nfn f() {{}}  \r\n")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("outdated.rs")).unwrap(),
+            format!("{header}\r\nfn f() {{}}\r\n")
+        );
+    }
+
+    #[test]
+    fn template() {
+        let headers = headers("template", "author = 'Me'\ntemplates.toml = 'T'\n");
+
+        assert_eq!(headers.template("toml"), Some("T"));
+        assert_eq!(headers.template("rs"), None);
+        assert_eq!(
+            headers.substitute("{year} {author}"),
+            [format!("{} Me", headers.year)]
+        );
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,410 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+// The trait is required by the `write!` macro for the summaries.
+use std::fmt::Write as _;
+
+/// The history of the runs of this application.
+///
+/// Each run is appended as one line of JSON to the file `history.jsonl` within
+/// the directory `optimise/` in the target directory.  A record names the time
+/// of the run, its exit code and, for each step, its status, its duration and
+/// the number of diagnostics per lint, if they were collected.  The runs with
+/// another toolchain, see `--toolchains`, are recorded in the same file but
+/// labelled with the toolchain such that their trends are kept apart.
+pub struct History {
+    /// The file to store the records in.
+    file: std::path::PathBuf,
+
+    /// The toolchain the recorded runs used, unless it is the default one.
+    toolchain: Option<String>,
+}
+
+/// The record of a single run.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Record {
+    /// The exit code of the run.
+    code: u8,
+
+    /// The records of the steps in their order of appearance.
+    steps: Vec<StepRecord>,
+
+    /// The seconds since the Unix epoch the run finished at.
+    timestamp: u64,
+
+    /// The toolchain the run used, unless it is the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    toolchain: Option<String>,
+}
+
+/// The record of a single step within a run.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct StepRecord {
+    /// The wall-clock time the step took in seconds.
+    duration: f64,
+
+    /// The number of diagnostics per lint, if they were collected.
+    lints: Option<std::collections::BTreeMap<String, usize>>,
+
+    /// The name of the step.
+    name: String,
+
+    /// The status of the step.
+    status: String,
+}
+
+impl History {
+    /// Append the given report to the history.
+    ///
+    /// # Errors
+    ///
+    /// If the history file cannot be written, the underlying I/O error will be
+    /// returned.
+    pub fn append(&self, report: &crate::Report) -> std::io::Result<()> {
+        let record = Record {
+            code: report.code() as u8,
+            steps: report
+                .outcomes()
+                .iter()
+                .map(|outcome| StepRecord {
+                    duration: outcome.duration().as_secs_f64(),
+                    lints: outcome.diagnostics().map(|diagnostics| {
+                        let mut lints = std::collections::BTreeMap::new();
+
+                        for lint in diagnostics.iter().filter_map(crate::Diagnostic::lint) {
+                            *lints.entry(lint.to_string()).or_default() += 1;
+                        }
+
+                        lints
+                    }),
+                    name: outcome.name().into(),
+                    status: outcome.status().to_string(),
+                })
+                .collect(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            toolchain: self.toolchain.clone(),
+        };
+        let line = serde_json::to_string(&record)?;
+
+        if let Some(directory) = self.file.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        std::io::Write::write_fmt(
+            &mut std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&self.file)?,
+            format_args!("{line}\n"),
+        )
+    }
+
+    /// Configure the history for the given workspace.
+    #[must_use]
+    pub fn new(workspace: &crate::Workspace) -> Self {
+        Self {
+            file: workspace.target().join("optimise").join("history.jsonl"),
+            toolchain: None,
+        }
+    }
+
+    /// Read the records of the toolchain from the history file.
+    ///
+    /// Malformed lines are silently skipped.  A missing history file is
+    /// considered an empty history.
+    fn records(&self) -> std::io::Result<Vec<Record>> {
+        match std::fs::read_to_string(&self.file) {
+            Ok(content) => Ok(content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Record>(line).ok())
+                .filter(|record| record.toolchain == self.toolchain)
+                .collect()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Draw a sparkline of the given values.
+    ///
+    /// Unknown values are drawn as a space character.
+    fn sparkline(values: &[Option<f64>]) -> String {
+        const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let max = values
+            .iter()
+            .flatten()
+            .fold(0.0_f64, |max, &value| max.max(value));
+
+        values
+            .iter()
+            .map(|value| match value {
+                None => ' ',
+                Some(_) if max <= 0.0 => BARS[0],
+                Some(value) => {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let index = (value / max * 7.0).round() as usize;
+                    BARS[index.min(7)]
+                }
+            })
+            .collect()
+    }
+
+    /// Derive the history of the runs with the given toolchain.
+    #[must_use]
+    pub fn toolchain(&self, toolchain: &str) -> Self {
+        Self {
+            file: self.file.clone(),
+            toolchain: Some(toolchain.into()),
+        }
+    }
+
+    /// Summarise the trends over the given number of most recent runs.
+    ///
+    /// The summary consists of three sections:
+    ///
+    /// * the durations of the steps, omitting those which were not run,
+    /// * the number of diagnostics per step and lint, and
+    /// * the steps failing in the latest run, together with the first run of
+    ///   the uninterrupted sequence of runs they failed in.
+    ///
+    /// # Errors
+    ///
+    /// If the history file cannot be read, the underlying I/O error will be
+    /// returned.
+    pub fn trends(&self, runs: usize) -> std::io::Result<String> {
+        let records = self.records()?;
+
+        let Some(latest) = records.last() else {
+            return Ok("There are no runs recorded, yet.".into());
+        };
+
+        let first = records.len().saturating_sub(runs.max(1));
+        let mut summary = format!(
+            "Trends over the last {} of {} runs, from {} to {}.",
+            records.len() - first,
+            records.len(),
+            crate::date(records[first].timestamp),
+            crate::date(latest.timestamp),
+        );
+
+        Self::trend_durations(&mut summary, &records[first..]);
+        Self::trend_lints(&mut summary, &records, first);
+        Self::trend_failures(&mut summary, &records);
+
+        Ok(summary)
+    }
+
+    /// Summarise the durations of the steps within the given runs.
+    ///
+    /// Only steps which were actually run are considered.
+    fn trend_durations(summary: &mut String, window: &[Record]) {
+        let mut steps = Vec::<&str>::new();
+
+        for step in window.iter().flat_map(|record| &record.steps) {
+            if !steps.contains(&step.name.as_str()) {
+                steps.push(&step.name);
+            }
+        }
+
+        let width = Self::width(steps.iter().copied(), "step");
+        let seconds =
+            |value: Option<f64>| value.map_or_else(|| "-".into(), |value| format!("{value:.2}s"));
+
+        summary.push_str("\n\nDurations:");
+        let _ = write!(
+            summary,
+            "\n{:width$}  {:>9}  {:>9}  {:>9}  {:>9}  trend",
+            "step", "latest", "mean", "min", "max"
+        );
+
+        for step in steps {
+            let durations = window
+                .iter()
+                .map(|record| {
+                    record
+                        .steps
+                        .iter()
+                        .find(|record| record.name == step && record.ran())
+                        .map(|record| record.duration)
+                })
+                .collect::<Vec<Option<f64>>>();
+            let measured = durations.iter().flatten().copied().collect::<Vec<f64>>();
+
+            #[allow(clippy::cast_precision_loss)]
+            let mean = (!measured.is_empty())
+                .then(|| measured.iter().sum::<f64>() / measured.len() as f64);
+
+            let _ = write!(
+                summary,
+                "\n{step:width$}  {:>9}  {:>9}  {:>9}  {:>9}  {}",
+                seconds(durations.last().copied().flatten()),
+                seconds(mean),
+                seconds(measured.iter().copied().reduce(f64::min)),
+                seconds(measured.iter().copied().reduce(f64::max)),
+                Self::sparkline(&durations)
+            );
+        }
+    }
+
+    /// Summarise the steps failing in the latest of the given runs.
+    ///
+    /// For each failing step, the first run of the uninterrupted sequence of
+    /// runs it failed in will be named.  Runs the step was not run in do not
+    /// interrupt this sequence.
+    fn trend_failures(summary: &mut String, records: &[Record]) {
+        let failing = records
+            .last()
+            .map(|record| {
+                record
+                    .steps
+                    .iter()
+                    .filter(|step| step.failed())
+                    .collect::<Vec<&StepRecord>>()
+            })
+            .unwrap_or_default();
+        let width = Self::width(failing.iter().map(|step| step.name.as_str()), "step");
+
+        summary.push_str("\n\nFailures:");
+
+        if failing.is_empty() {
+            summary.push_str("\nNo step failed in the latest run.");
+        }
+
+        for step in failing {
+            let mut since = records.len() - 1;
+
+            for (index, record) in records.iter().enumerate().rev() {
+                match record.steps.iter().find(|record| record.name == step.name) {
+                    Some(record) if record.failed() => since = index,
+                    Some(record) if record.succeeded() => break,
+                    _ => (),
+                }
+            }
+
+            let _ = write!(
+                summary,
+                "\n{:width$}  failing since {} (run {} of {})",
+                step.name,
+                crate::date(records[since].timestamp),
+                since + 1,
+                records.len()
+            );
+        }
+    }
+
+    /// Summarise the diagnostics per step and lint within the given runs.
+    ///
+    /// Only the runs starting at the given index are considered.  The earlier
+    /// runs are required since cached steps are known to report the same
+    /// diagnostics as in their last run such that their most recent lint counts
+    /// are carried forward.
+    fn trend_lints(summary: &mut String, records: &[Record], first: usize) {
+        let mut known =
+            std::collections::BTreeMap::<&str, &std::collections::BTreeMap<String, usize>>::new();
+        let mut lints = std::collections::BTreeMap::<(&str, &str), Vec<Option<f64>>>::new();
+
+        for (index, record) in records.iter().enumerate() {
+            for step in &record.steps {
+                if let Some(counts) = &step.lints {
+                    known.insert(&step.name, counts);
+                } else if step.status != "cached" {
+                    known.remove(step.name.as_str());
+                }
+            }
+
+            if index < first {
+                continue;
+            }
+
+            for (step, counts) in &known {
+                for lint in counts.keys() {
+                    lints
+                        .entry((step, lint))
+                        .or_insert_with(|| vec![None; records.len() - first]);
+                }
+            }
+
+            for ((step, lint), values) in &mut lints {
+                #[allow(clippy::cast_precision_loss)]
+                let count = known
+                    .get(step)
+                    .map(|counts| counts.get(*lint).copied().unwrap_or_default() as f64);
+                values[index - first] = count;
+            }
+        }
+
+        summary.push_str("\n\nDiagnostics:");
+
+        if lints.is_empty() {
+            summary.push_str("\nThere are no diagnostics recorded.");
+            return;
+        }
+
+        let width = Self::width(lints.keys().map(|(step, _)| *step), "step");
+        let lint_width = Self::width(lints.keys().map(|(_, lint)| *lint), "lint");
+        let count = |value: Option<&f64>| value.map_or_else(|| "-".into(), ToString::to_string);
+
+        let _ = write!(
+            summary,
+            "\n{:width$}  {:lint_width$}  {:>6}  {:>6}  trend",
+            "step", "lint", "first", "latest"
+        );
+
+        for ((step, lint), values) in &lints {
+            let _ = write!(
+                summary,
+                "\n{step:width$}  {lint:lint_width$}  {:>6}  {:>6}  {}",
+                count(values.iter().flatten().next()),
+                count(values.iter().flatten().last()),
+                Self::sparkline(values)
+            );
+        }
+    }
+
+    /// The width of the widest of the given entries and the given heading.
+    fn width<'a>(entries: impl Iterator<Item = &'a str>, heading: &str) -> usize {
+        entries
+            .map(str::len)
+            .chain(std::iter::once(heading.len()))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl StepRecord {
+    /// Whether the step failed.
+    fn failed(&self) -> bool {
+        self.status == "failed"
+    }
+
+    /// Whether the step was run, regardless of its success.
+    fn ran(&self) -> bool {
+        self.status == "passed" || self.failed()
+    }
+
+    /// Whether the step succeeded, either in this run or in a cached one.
+    fn succeeded(&self) -> bool {
+        self.status == "passed" || self.status == "cached"
+    }
+}
+
+/******************************************************************************/
@@ -35,16 +35,92 @@
 
 // Module imports.
 mod application;
+mod audit;
+mod cache;
+mod changelog;
+mod check;
 mod cli_options;
+mod comment_style;
+mod condition;
+mod config;
+mod context;
+mod cross;
+mod diagnostic;
+mod duplicates;
+mod expectation;
+mod features;
+mod findings;
 mod functions;
+mod headers;
+mod history;
+mod license_expression;
+mod licenses;
+mod matrix;
+mod metadata;
+mod msrv;
+mod pipeline;
+mod plugin;
 mod process;
+mod process_step;
+mod report;
+mod resource_usage;
+mod semver_check;
+mod snapshot;
+mod step;
+mod step_outcome;
+mod summary;
+mod test_results;
+mod toolchain;
+mod unused;
 mod verbosity;
+mod watch;
+mod workspace;
 
 // Module exports.
 pub use crate::application::Application;
-pub use crate::cli_options::CliOptions;
-pub use crate::functions::license;
-pub use crate::process::Process;
+pub use crate::context::Context;
+pub use crate::step::Step;
+pub use crate::step_outcome::{Status, StepOutcome};
 pub use crate::verbosity::{ParseError as VerbosityParseError, Verbosity};
 
+// Crate internal exports.
+pub(crate) use crate::audit::Audit;
+pub(crate) use crate::cache::Cache;
+pub(crate) use crate::changelog::Changelog;
+pub(crate) use crate::check::Check;
+pub(crate) use crate::cli_options::{CliOptions, Command};
+pub(crate) use crate::comment_style::CommentStyle;
+pub(crate) use crate::condition::Condition;
+pub(crate) use crate::config::{
+    AuditConfig, ChangelogConfig, Config, DuplicatesConfig, FeaturesConfig, HeadersConfig,
+    LicensesConfig, ProfileConfig, UnusedConfig,
+};
+pub(crate) use crate::cross::Cross;
+pub(crate) use crate::diagnostic::Diagnostic;
+pub(crate) use crate::duplicates::Duplicates;
+pub(crate) use crate::expectation::Expectation;
+pub(crate) use crate::features::Features;
+pub(crate) use crate::findings::Findings;
+pub(crate) use crate::functions::{date, license};
+pub(crate) use crate::headers::Headers;
+pub(crate) use crate::history::History;
+pub(crate) use crate::license_expression::LicenseExpression;
+pub(crate) use crate::licenses::Licenses;
+pub(crate) use crate::matrix::Matrix;
+pub(crate) use crate::metadata::{Dependency, Metadata, Package};
+pub(crate) use crate::msrv::Msrv;
+pub(crate) use crate::plugin::Plugin;
+pub(crate) use crate::process::Process;
+pub(crate) use crate::process_step::ProcessStep;
+pub(crate) use crate::report::Report;
+pub(crate) use crate::resource_usage::ResourceUsage;
+pub(crate) use crate::semver_check::SemverCheck;
+pub(crate) use crate::snapshot::{FileChange, Snapshot};
+pub(crate) use crate::summary::Summary;
+pub(crate) use crate::test_results::TestResults;
+pub(crate) use crate::toolchain::Toolchain;
+pub(crate) use crate::unused::Unused;
+pub(crate) use crate::watch::Watch;
+pub(crate) use crate::workspace::Workspace;
+
 /******************************************************************************/
@@ -0,0 +1,218 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A parsed SPDX license expression.
+///
+/// The operators are bound in the order `WITH`, `AND` and `OR`, from the
+/// tightest to the loosest.  The legacy separator `/` found in older manifests
+/// is understood as `OR`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LicenseExpression {
+    /// Both licenses need to be complied with.
+    And(Box<Self>, Box<Self>),
+
+    /// A single license, optionally with an exception.
+    License {
+        /// The exception to the license, if any.
+        exception: Option<String>,
+
+        /// The identifier of the license, such as `MIT`.
+        identifier: String,
+    },
+
+    /// Either license can be chosen.
+    Or(Box<Self>, Box<Self>),
+}
+
+impl LicenseExpression {
+    /// Parse the given SPDX license expression.
+    ///
+    /// If the expression should be malformed, `None` will be returned.
+    #[must_use]
+    pub fn parse(expression: &str) -> Option<Self> {
+        let spaced = expression
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .replace('/', " OR ");
+        let tokens = spaced.split_whitespace().collect::<Vec<&str>>();
+        let mut position = 0;
+        let parsed = Self::parse_or(&tokens, &mut position)?;
+
+        (position == tokens.len()).then_some(parsed)
+    }
+
+    /// Parse a conjunction of licenses.
+    fn parse_and(tokens: &[&str], position: &mut usize) -> Option<Self> {
+        let mut left = Self::parse_with(tokens, position)?;
+
+        while tokens.get(*position) == Some(&"AND") {
+            *position += 1;
+            left = Self::And(
+                Box::new(left),
+                Box::new(Self::parse_with(tokens, position)?),
+            );
+        }
+
+        Some(left)
+    }
+
+    /// Parse a disjunction of licenses.
+    fn parse_or(tokens: &[&str], position: &mut usize) -> Option<Self> {
+        let mut left = Self::parse_and(tokens, position)?;
+
+        while tokens.get(*position) == Some(&"OR") {
+            *position += 1;
+            left = Self::Or(Box::new(left), Box::new(Self::parse_and(tokens, position)?));
+        }
+
+        Some(left)
+    }
+
+    /// Parse a single license or a parenthesised expression.
+    fn parse_with(tokens: &[&str], position: &mut usize) -> Option<Self> {
+        let token = *tokens.get(*position)?;
+        *position += 1;
+
+        if token == "(" {
+            let inner = Self::parse_or(tokens, position)?;
+
+            if tokens.get(*position) != Some(&")") {
+                return None;
+            }
+
+            *position += 1;
+            return Some(inner);
+        }
+
+        if [")", "AND", "OR", "WITH"].contains(&token) {
+            return None;
+        }
+
+        let exception = if tokens.get(*position) == Some(&"WITH") {
+            let exception = tokens.get(*position + 1)?;
+            *position += 2;
+            Some((*exception).to_string())
+        } else {
+            None
+        };
+
+        Some(Self::License {
+            exception,
+            identifier: token.into(),
+        })
+    }
+}
+
+impl std::fmt::Display for LicenseExpression {
+    /// Implements the `Display` trait.
+    ///
+    /// An expression will be formatted in its canonical SPDX notation with
+    /// compound operands being parenthesised.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operand = |expression: &Self| match expression {
+            Self::License { .. } => expression.to_string(),
+            _ => format!("({expression})"),
+        };
+
+        match self {
+            Self::And(left, right) => write!(f, "{} AND {}", operand(left), operand(right)),
+            Self::License {
+                exception: Some(exception),
+                identifier,
+            } => write!(f, "{identifier} WITH {exception}"),
+            Self::License {
+                exception: None,
+                identifier,
+            } => write!(f, "{identifier}"),
+            Self::Or(left, right) => write!(f, "{} OR {}", operand(left), operand(right)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LicenseExpression;
+
+    fn license(identifier: &str, exception: Option<&str>) -> LicenseExpression {
+        LicenseExpression::License {
+            exception: exception.map(Into::into),
+            identifier: identifier.into(),
+        }
+    }
+
+    #[test]
+    fn parse_malformed() {
+        for expression in ["", "MIT AND", "(MIT", "MIT)", "MIT WITH", "OR MIT", "()"] {
+            assert_eq!(LicenseExpression::parse(expression), None, "{expression}");
+        }
+    }
+
+    #[test]
+    fn parse_parentheses() {
+        let expression = LicenseExpression::parse("(MIT OR Apache-2.0) AND ISC").unwrap();
+
+        assert_eq!(
+            expression,
+            LicenseExpression::And(
+                Box::new(LicenseExpression::Or(
+                    Box::new(license("MIT", None)),
+                    Box::new(license("Apache-2.0", None)),
+                )),
+                Box::new(license("ISC", None)),
+            )
+        );
+        assert_eq!(expression.to_string(), "(MIT OR Apache-2.0) AND ISC");
+    }
+
+    #[test]
+    fn parse_precedence() {
+        assert_eq!(
+            LicenseExpression::parse("MIT OR Apache-2.0 AND ISC"),
+            Some(LicenseExpression::Or(
+                Box::new(license("MIT", None)),
+                Box::new(LicenseExpression::And(
+                    Box::new(license("Apache-2.0", None)),
+                    Box::new(license("ISC", None)),
+                )),
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_slash() {
+        assert_eq!(
+            LicenseExpression::parse("MIT/Apache-2.0"),
+            LicenseExpression::parse("MIT OR Apache-2.0")
+        );
+    }
+
+    #[test]
+    fn parse_with() {
+        assert_eq!(
+            LicenseExpression::parse("GPL-2.0-or-later WITH Classpath-exception-2.0 OR MIT"),
+            Some(LicenseExpression::Or(
+                Box::new(license("GPL-2.0-or-later", Some("Classpath-exception-2.0"))),
+                Box::new(license("MIT", None)),
+            ))
+        );
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,236 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A check of the licenses of the dependencies against a policy.
+///
+/// The license of each dependency is read as an SPDX expression from its
+/// manifest.  A license is complied with if it is allowed and not denied by the
+/// policy.  For `OR`, one of the alternatives needs to be complied with while
+/// for `AND`, all of the operands need to be.  A license with an exception is
+/// judged by the license itself unless the combination is listed explicitly.
+#[derive(Clone)]
+pub struct Licenses {
+    /// The licenses to comply with.
+    allow: Vec<String>,
+
+    /// The licenses to never comply with.
+    deny: Vec<String>,
+
+    /// The names of the packages not to check.
+    ignore: Vec<String>,
+
+    /// The file to write the inventory of third-party licenses to, if any.
+    inventory: Option<std::path::PathBuf>,
+
+    /// The information about the workspace to check.
+    metadata: std::sync::Arc<crate::Metadata>,
+}
+
+/// The judgement of a license expression by the policy.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    /// The expression can be complied with.
+    Allowed,
+
+    /// The expression cannot be complied with.
+    Denied,
+
+    /// The expression is not covered by the policy.
+    Unknown,
+}
+
+impl Licenses {
+    /// Judge the given expression by the policy.
+    fn evaluate(&self, expression: &crate::LicenseExpression) -> Verdict {
+        match expression {
+            crate::LicenseExpression::And(left, right) => {
+                match (self.evaluate(left), self.evaluate(right)) {
+                    (Verdict::Denied, _) | (_, Verdict::Denied) => Verdict::Denied,
+                    (Verdict::Unknown, _) | (_, Verdict::Unknown) => Verdict::Unknown,
+                    _ => Verdict::Allowed,
+                }
+            }
+            crate::LicenseExpression::License { identifier, .. } => {
+                let listed = |list: &[String]| {
+                    let full = expression.to_string();
+                    list.iter()
+                        .any(|license| license == &full || license == identifier)
+                };
+
+                if listed(&self.deny) {
+                    Verdict::Denied
+                } else if listed(&self.allow) {
+                    Verdict::Allowed
+                } else {
+                    Verdict::Unknown
+                }
+            }
+            crate::LicenseExpression::Or(left, right) => {
+                match (self.evaluate(left), self.evaluate(right)) {
+                    (Verdict::Allowed, _) | (_, Verdict::Allowed) => Verdict::Allowed,
+                    (Verdict::Unknown, _) | (_, Verdict::Unknown) => Verdict::Unknown,
+                    _ => Verdict::Denied,
+                }
+            }
+        }
+    }
+
+    /// Whether the check writes to the workspace.
+    #[must_use]
+    pub const fn is_mutating(&self) -> bool {
+        self.inventory.is_some()
+    }
+
+    /// Configure a new check of the licenses.
+    ///
+    /// A relative path to the inventory is resolved against the workspace
+    /// root.
+    #[must_use]
+    pub fn new(config: &crate::LicensesConfig, metadata: std::sync::Arc<crate::Metadata>) -> Self {
+        Self {
+            allow: config.allow().to_vec(),
+            deny: config.deny().to_vec(),
+            ignore: config.ignore().to_vec(),
+            inventory: config
+                .inventory()
+                .map(|file| metadata.workspace().root().join(file)),
+            metadata,
+        }
+    }
+
+    /// Check the licenses of all dependencies.
+    ///
+    /// Dependencies without a license, with a malformed expression or with a
+    /// license not covered by the policy are reported as errors, as well as
+    /// those whose license is denied.  If configured, the inventory of the
+    /// licenses will be written afterwards.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let members = self.metadata.members();
+        let mut packages = self
+            .metadata
+            .packages()
+            .iter()
+            .filter(|package| !members.iter().any(|member| member.id() == package.id()))
+            .collect::<Vec<&crate::Package>>();
+        packages.sort_by(|a, b| a.name().cmp(b.name()).then(a.version().cmp(b.version())));
+
+        for package in &packages {
+            if self.ignore.iter().any(|name| name == package.name()) {
+                continue;
+            }
+
+            let name = format!("{} {}", package.name(), package.version());
+
+            match package.license() {
+                None if package.license_file().is_some() => findings.error(format!(
+                    "{name}:  license given by a file instead of an SPDX expression"
+                )),
+                None => findings.error(format!("{name}:  no license declared")),
+                Some(license) => match crate::LicenseExpression::parse(license) {
+                    None => findings.error(format!("{name}:  malformed license `{license}`")),
+                    Some(expression) => match self.evaluate(&expression) {
+                        Verdict::Allowed => {}
+                        Verdict::Denied => {
+                            findings.error(format!("{name}:  denied license `{expression}`"));
+                        }
+                        Verdict::Unknown => findings.error(format!(
+                            "{name}:  license `{expression}` not covered by the policy"
+                        )),
+                    },
+                },
+            }
+        }
+
+        if let Some(file) = &self.inventory {
+            if Self::write_inventory(file, &packages).is_err() {
+                findings.error(format!(
+                    "failed to write the license inventory to '{}'",
+                    file.display()
+                ));
+            }
+        }
+
+        findings
+    }
+
+    /// Write the inventory of the licenses of the given packages.
+    fn write_inventory(
+        file: &std::path::Path,
+        packages: &[&crate::Package],
+    ) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(file)?);
+
+        std::io::Write::write_fmt(
+            &mut writer,
+            format_args!(
+                "# Third-Party Licenses\n\n| Package | Version | License |\n| :--- | :--- | :--- |\n"
+            ),
+        )?;
+
+        for package in packages {
+            std::io::Write::write_fmt(
+                &mut writer,
+                format_args!(
+                    "| {} | {} | {} |\n",
+                    package.name(),
+                    package.version(),
+                    package
+                        .license()
+                        .or_else(|| package.license_file().map(|_| "see license file"))
+                        .unwrap_or("unknown")
+                ),
+            )?;
+        }
+
+        std::io::Write::flush(&mut writer)
+    }
+}
+
+impl std::fmt::Display for Licenses {
+    /// Implements the `Display` trait.
+    ///
+    /// A check will be formatted like a command line naming the policy as well
+    /// as the inventory.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "licenses")?;
+
+        for license in &self.allow {
+            write!(f, " --allow '{license}'")?;
+        }
+
+        for license in &self.deny {
+            write!(f, " --deny '{license}'")?;
+        }
+
+        for name in &self.ignore {
+            write!(f, " --ignore {name}")?;
+        }
+
+        if let Some(file) = &self.inventory {
+            write!(f, " --inventory {}", file.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+/******************************************************************************/
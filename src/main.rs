@@ -18,78 +18,17 @@
 |                                                                              |
 \******************************************************************************/
 
-// Third party libraries.
-use clap::Parser;
+//! The binary root of this crate.
+//!
+//! It provides the Cargo subcommand `cargo optimise` which composes the steps
+//! defined by the library and runs them on the current project.
 
 /// The main function.
 ///
-/// It composes the required functionalities and takes care for the error event
-/// handling as well as the return status.
+/// It passes the command line arguments to the application which takes care
+/// for the error event handling as well as the return status.
 fn main() -> sysexits::ExitCode {
-    const PROCESSES: usize = 5;
-
-    let args = rs_optimise::CliOptions::parse();
-
-    if args.license() {
-        rs_optimise::license();
-        return sysexits::ExitCode::Ok;
-    }
-
-    let applications = vec!["cargo".into(); PROCESSES];
-
-    let mut arguments = vec![vec!["metadata".into()]];
-    arguments.push(vec![
-        "clippy".into(),
-        "--fix".into(),
-        "--allow-dirty".into(),
-        "--allow-staged".into(),
-    ]);
-    arguments.push(vec!["fmt".into()]);
-    arguments.push(vec!["check".into()]);
-    arguments.push(vec![
-        "clippy".into(),
-        "--".into(),
-        "-D".into(),
-        "clippy::all".into(),
-        "-D".into(),
-        "clippy::cargo".into(),
-        "-D".into(),
-        "clippy::complexity".into(),
-        "-D".into(),
-        "clippy::correctness".into(),
-        "-D".into(),
-        "clippy::nursery".into(),
-        "-D".into(),
-        "clippy::perf".into(),
-        "-D".into(),
-        "clippy::pedantic".into(),
-        "-D".into(),
-        "clippy::suspicious".into(),
-        "-D".into(),
-        "clippy::style".into(),
-    ]);
-    let arguments = arguments;
-
-    let mut error_messages = vec![None; PROCESSES];
-    error_messages[0] = Some("This is not a Cargo maintained Rust project".into());
-    let error_messages = error_messages;
-
-    let mut exit_codes = vec![sysexits::ExitCode::DataErr; PROCESSES];
-    exit_codes[0] = sysexits::ExitCode::Usage;
-    let exit_codes = exit_codes;
-
-    let mut verbosities = vec![args.verbosity(); PROCESSES];
-    verbosities[0].silent();
-    let verbosities = verbosities;
-
-    rs_optimise::Application::new(
-        applications,
-        arguments,
-        error_messages,
-        exit_codes,
-        verbosities,
-    )
-    .run()
+    rs_optimise::Application::main(std::env::args().collect())
 }
 
 /******************************************************************************/
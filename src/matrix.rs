@@ -0,0 +1,147 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+// The trait is required by the `write!` macro for the table.
+use std::fmt::Write as _;
+
+/// A matrix of runs, one per variant of an `Application`.
+///
+/// Each column is a variant, such as the application run with another
+/// toolchain.  Variants which are not available locally are listed without
+/// being run.  The results are presented as a table of the steps and the
+/// variants.
+pub struct Matrix {
+    /// The variants together with their applications, if available.
+    columns: Vec<(String, Option<crate::Application>)>,
+
+    /// The verbosity of the run.
+    verbosity: crate::Verbosity,
+}
+
+impl Matrix {
+    /// Add a variant to run.
+    #[must_use]
+    pub fn column(mut self, name: &str, application: crate::Application) -> Self {
+        self.columns.push((name.into(), Some(application)));
+        self
+    }
+
+    /// Create a new, empty matrix.
+    #[must_use]
+    pub const fn new(verbosity: crate::Verbosity) -> Self {
+        Self {
+            columns: Vec::new(),
+            verbosity,
+        }
+    }
+
+    /// Run all available variants and present the matrix of their results.
+    ///
+    /// All variants are run, even if some of them fail, and each of them is
+    /// recorded, see `Application::record`.  The exit code of the first
+    /// failing variant is returned.  If a variant is not available, the
+    /// run is not considered failed.
+    #[must_use]
+    pub fn run(&self) -> sysexits::ExitCode {
+        let mut code = sysexits::ExitCode::Ok;
+        let mut reports = Vec::<(&str, Option<crate::Report>)>::new();
+
+        for (name, application) in &self.columns {
+            let report = application.as_ref().map(|application| {
+                if self.verbosity > crate::Verbosity::Silent {
+                    println!("\n==> {name} <==");
+                }
+
+                application.record()
+            });
+
+            if let Some(report) = &report {
+                if code.is_success() {
+                    code = report.code();
+                }
+            }
+
+            reports.push((name, report));
+        }
+
+        if self.verbosity > crate::Verbosity::Silent {
+            println!("\n{}", Self::render(&reports));
+        }
+
+        code
+    }
+
+    /// Render the results of the variants as a table.
+    fn render(reports: &[(&str, Option<crate::Report>)]) -> String {
+        let mut steps = Vec::<&str>::new();
+
+        for outcome in reports
+            .iter()
+            .filter_map(|(_, report)| report.as_ref())
+            .flat_map(crate::Report::outcomes)
+        {
+            if !steps.contains(&outcome.name()) {
+                steps.push(outcome.name());
+            }
+        }
+
+        let width = steps
+            .iter()
+            .map(|step| step.len())
+            .chain(std::iter::once("step".len()))
+            .max()
+            .unwrap_or_default();
+        let mut table = format!("{:width$}", "step");
+
+        for (name, _) in reports {
+            let _ = write!(table, "  {:>w$}", name, w = name.len().max(13));
+        }
+
+        for step in steps {
+            let _ = write!(table, "\n{step:width$}");
+
+            for (name, report) in reports {
+                let cell = report.as_ref().map_or_else(
+                    || "not installed".into(),
+                    |report| {
+                        report
+                            .outcomes()
+                            .iter()
+                            .find(|outcome| outcome.name() == step)
+                            .map_or_else(|| "-".into(), |outcome| outcome.status().to_string())
+                    },
+                );
+
+                let _ = write!(table, "  {:>w$}", cell, w = name.len().max(13));
+            }
+        }
+
+        table
+    }
+
+    /// Add a variant which is not available locally.
+    #[must_use]
+    pub fn unavailable(mut self, name: &str) -> Self {
+        self.columns.push((name.into(), None));
+        self
+    }
+}
+
+/******************************************************************************/
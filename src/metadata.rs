@@ -0,0 +1,415 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The information Cargo provides about the current workspace.
+///
+/// These information are read from the output of `cargo metadata`.  Only the
+/// fields required by this application are considered.
+#[derive(Clone, serde::Deserialize)]
+pub struct Metadata {
+    /// The packages of the workspace and their dependencies.
+    packages: Vec<Package>,
+
+    /// The resolved dependency graph of the workspace.
+    resolve: Option<Resolve>,
+
+    /// The directory Cargo writes its build artifacts to.
+    target_directory: std::path::PathBuf,
+
+    /// The identifiers of the packages which are members of the workspace.
+    workspace_members: Vec<String>,
+
+    /// The directory containing the workspace manifest.
+    workspace_root: std::path::PathBuf,
+}
+
+/// A package known to Cargo.
+#[derive(Clone, serde::Deserialize)]
+pub struct Package {
+    /// The dependencies declared in the manifest of the package.
+    dependencies: Vec<Dependency>,
+
+    /// The features of the package together with the features they enable.
+    features: std::collections::BTreeMap<String, Vec<String>>,
+
+    /// The unique identifier of the package.
+    id: String,
+
+    /// The SPDX expression of the license of the package, if declared.
+    license: Option<String>,
+
+    /// The path to the license file of the package, if declared.
+    license_file: Option<std::path::PathBuf>,
+
+    /// The path to the manifest of the package.
+    manifest_path: std::path::PathBuf,
+
+    /// The name of the package.
+    name: String,
+
+    /// The minimum supported Rust version of the package, if declared.
+    rust_version: Option<String>,
+
+    /// The source of the package, such as a registry, unless it is local.
+    source: Option<String>,
+
+    /// The targets of the package.
+    targets: Vec<Target>,
+
+    /// The version of the package.
+    version: String,
+}
+
+/// A dependency declared in the manifest of a package.
+#[derive(Clone, serde::Deserialize)]
+pub struct Dependency {
+    /// The kind of the dependency, unless it is a normal one.
+    kind: Option<String>,
+
+    /// The name of the package depended on.
+    name: String,
+
+    /// Whether the dependency is only enabled by a feature.
+    optional: bool,
+
+    /// The name the dependency was renamed to, if so.
+    rename: Option<String>,
+}
+
+/// The resolved dependency graph of a workspace.
+#[derive(Clone, serde::Deserialize)]
+struct Resolve {
+    /// The packages of the graph together with their resolved dependencies.
+    nodes: Vec<Node>,
+}
+
+/// A package within the resolved dependency graph.
+#[derive(Clone, serde::Deserialize)]
+struct Node {
+    /// The identifiers of the resolved dependencies of the package.
+    dependencies: Vec<String>,
+
+    /// The identifier of the package.
+    id: String,
+}
+
+/// A target of a package, such as a library or a binary.
+#[derive(Clone, serde::Deserialize)]
+pub struct Target {
+    /// Whether the documentation examples of this target are tested.
+    #[serde(default)]
+    doctest: bool,
+
+    /// The kinds of this target, such as `lib` or `bin`.
+    kind: Vec<String>,
+}
+
+impl Dependency {
+    /// The identifier the dependency is referred to with in the sources.
+    ///
+    /// This is the name of the package or the name it was renamed to, with
+    /// all hyphens replaced by underscores.
+    #[must_use]
+    pub fn identifier(&self) -> String {
+        self.rename.as_ref().unwrap_or(&self.name).replace('-', "_")
+    }
+
+    /// The kind of the dependency, such as `dev` or `build`, unless it is a
+    /// normal one.
+    #[must_use]
+    pub fn kind(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    /// The name the dependency is declared with in the manifest.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        self.rename.as_ref().unwrap_or(&self.name)
+    }
+
+    /// The name of the package depended on.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the dependency is only enabled by a feature.
+    #[must_use]
+    pub const fn optional(&self) -> bool {
+        self.optional
+    }
+}
+
+impl Metadata {
+    /// The packages directly depending on the given package.
+    #[must_use]
+    pub fn dependents(&self, id: &str) -> Vec<&Package> {
+        self.resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .filter(|node| node.dependencies.iter().any(|dependency| dependency == id))
+            .filter_map(|node| self.package(&node.id))
+            .collect()
+    }
+
+    /// The shortest chain of dependencies from a workspace member to the given
+    /// package.
+    ///
+    /// The chain starts with the workspace member and ends with the package
+    /// itself.  If the package is not reachable, the chain will be empty.
+    #[must_use]
+    pub fn dependency_path(&self, id: &str) -> Vec<&Package> {
+        let nodes = self
+            .resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .map(|node| (node.id.as_str(), node.dependencies.as_slice()))
+            .collect::<std::collections::HashMap<&str, &[String]>>();
+        let mut parents = std::collections::HashMap::<&str, &str>::new();
+        let mut queue = self
+            .workspace_members
+            .iter()
+            .map(String::as_str)
+            .collect::<std::collections::VecDeque<&str>>();
+
+        for member in &queue {
+            parents.insert(member, member);
+        }
+
+        while let Some(current) = queue.pop_front() {
+            if current == id {
+                let mut path = vec![current];
+
+                while let Some(&parent) = parents.get(path[path.len() - 1]) {
+                    if parent == path[path.len() - 1] {
+                        break;
+                    }
+
+                    path.push(parent);
+                }
+
+                return path
+                    .into_iter()
+                    .rev()
+                    .filter_map(|id| self.package(id))
+                    .collect();
+            }
+
+            for dependency in nodes.get(current).copied().unwrap_or_default() {
+                if !parents.contains_key(dependency.as_str()) {
+                    parents.insert(dependency, current);
+                    queue.push_back(dependency);
+                }
+            }
+        }
+
+        Vec::new()
+    }
+
+    /// Whether at least one member of the workspace has documentation tests.
+    #[must_use]
+    pub fn has_doctests(&self) -> bool {
+        self.members()
+            .iter()
+            .flat_map(|package| package.targets())
+            .any(Target::doctest)
+    }
+
+    /// The information about the workspace restricted to the host platform.
+    ///
+    /// Only the dependencies which are built for the platform of the active
+    /// toolchain are resolved.  If the host platform cannot be determined or
+    /// Cargo fails, `None` will be returned.
+    #[must_use]
+    pub fn host(&self) -> Option<Self> {
+        let mut rustc = crate::Process::new("rustc", vec!["-vV".into()], crate::Verbosity::Silent)
+            .current_dir(&self.workspace_root);
+
+        if rustc.run().is_failure() || !rustc.success() {
+            return None;
+        }
+
+        let host = rustc
+            .stdout()
+            .lines()
+            .find_map(|line| line.strip_prefix("host: "))?
+            .to_string();
+        let mut cargo = crate::Process::new(
+            "cargo",
+            [
+                "metadata",
+                "--format-version",
+                "1",
+                "--filter-platform",
+                &host,
+            ]
+            .map(String::from)
+            .to_vec(),
+            crate::Verbosity::Silent,
+        )
+        .current_dir(&self.workspace_root);
+
+        if cargo.run().is_failure() || !cargo.success() {
+            return None;
+        }
+
+        serde_json::from_str(cargo.stdout()).ok()
+    }
+
+    /// The packages which are members of the workspace.
+    #[must_use]
+    pub fn members(&self) -> Vec<&Package> {
+        self.packages
+            .iter()
+            .filter(|package| self.workspace_members.contains(&package.id))
+            .collect()
+    }
+
+    /// The package with the given identifier, if known.
+    #[must_use]
+    pub fn package(&self, id: &str) -> Option<&Package> {
+        self.packages.iter().find(|package| package.id == id)
+    }
+
+    /// The packages of the workspace and their dependencies.
+    #[must_use]
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    /// Query the information about the workspace the current working directory
+    /// belongs to.
+    ///
+    /// # Errors
+    ///
+    /// In case the current working directory should not belong to a Cargo
+    /// maintained project, the error will be reported to `stderr` and the
+    /// `sysexits::ExitCode` for `main` will be returned.  The same applies if
+    /// the output of `cargo metadata` should not be understood.
+    pub fn query() -> Result<Self, sysexits::ExitCode> {
+        let mut process = crate::Process::new(
+            "cargo",
+            vec!["metadata".into(), "--format-version".into(), "1".into()],
+            crate::Verbosity::Silent,
+        );
+
+        if let Some(code) = process.handle(
+            Some("This is not a Cargo maintained Rust project"),
+            sysexits::ExitCode::Usage,
+        ) {
+            return Err(code);
+        }
+
+        serde_json::from_str(process.stdout()).map_err(|_| {
+            eprintln!("Failed to understand the output of '{process}'!");
+            sysexits::ExitCode::DataErr
+        })
+    }
+
+    /// The location of the workspace.
+    #[must_use]
+    pub fn workspace(&self) -> crate::Workspace {
+        crate::Workspace::new(&self.workspace_root, &self.target_directory)
+    }
+}
+
+impl Package {
+    /// The dependencies declared in the manifest of the package.
+    #[must_use]
+    pub fn dependencies(&self) -> &[Dependency] {
+        &self.dependencies
+    }
+
+    /// The features of the package together with the features they enable.
+    #[must_use]
+    pub const fn features(&self) -> &std::collections::BTreeMap<String, Vec<String>> {
+        &self.features
+    }
+
+    /// The unique identifier of the package.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The SPDX expression of the license of the package, if declared.
+    #[must_use]
+    pub fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    /// The path to the license file of the package, if declared.
+    #[must_use]
+    pub fn license_file(&self) -> Option<&std::path::Path> {
+        self.license_file.as_deref()
+    }
+
+    /// The path to the manifest of the package.
+    #[must_use]
+    pub fn manifest_path(&self) -> &std::path::Path {
+        &self.manifest_path
+    }
+
+    /// The name of the package.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The minimum supported Rust version of the package, if declared.
+    #[must_use]
+    pub fn rust_version(&self) -> Option<&str> {
+        self.rust_version.as_deref()
+    }
+
+    /// The source of the package, such as a registry, unless it is local.
+    #[must_use]
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// The targets of the package.
+    #[must_use]
+    pub fn targets(&self) -> &[Target] {
+        &self.targets
+    }
+
+    /// The version of the package.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+impl Target {
+    /// Whether the documentation examples of this target are tested.
+    #[must_use]
+    pub const fn doctest(&self) -> bool {
+        self.doctest
+    }
+
+    /// The kinds of this target, such as `lib` or `bin`.
+    #[must_use]
+    pub fn kind(&self) -> &[String] {
+        &self.kind
+    }
+}
+
+/******************************************************************************/
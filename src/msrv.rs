@@ -0,0 +1,299 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A verification of the minimum supported Rust versions of the members.
+///
+/// Each member declaring a `rust-version` is checked with `cargo check` on a
+/// locally installed toolchain of that version.  If there is none, the check
+/// is skipped.  Each toolchain uses a dedicated target directory such that the
+/// regular build artifacts are not invalidated.
+#[derive(Clone)]
+pub struct Msrv {
+    /// The information about the workspace to check.
+    metadata: std::sync::Arc<crate::Metadata>,
+}
+
+impl Msrv {
+    /// Check the given packages with the given toolchain.
+    ///
+    /// If no packages are given, the whole workspace is checked.  On failure,
+    /// the error is returned, see `Process::verify`.
+    fn check(&self, toolchain: &crate::Toolchain, packages: &[&str]) -> Result<(), String> {
+        let target = self
+            .metadata
+            .workspace()
+            .target()
+            .join("optimise")
+            .join("msrv")
+            .join(toolchain.name());
+        let mut arguments = vec![
+            format!("+{}", toolchain.name()),
+            "check".into(),
+            "--target-dir".into(),
+            target.to_string_lossy().into(),
+        ];
+
+        if packages.is_empty() {
+            arguments.push("--workspace".into());
+        }
+
+        for package in packages {
+            arguments.push("-p".into());
+            arguments.push((*package).into());
+        }
+
+        crate::Process::new("cargo", arguments, crate::Verbosity::Silent)
+            .verify("cargo check failed")
+    }
+
+    /// Find the lowest locally installed toolchain the workspace compiles
+    /// with.
+    ///
+    /// The stable toolchains are bisected, assuming that the workspace compiles
+    /// with all toolchains newer than the lowest one it compiles with.  The
+    /// result is compared to the declared `rust-version` of the members, see
+    /// `verdict`.  If a declaration does not hold, `DataErr` is returned.
+    #[must_use]
+    pub fn find(&self, verbosity: crate::Verbosity) -> sysexits::ExitCode {
+        let mut toolchains = crate::Toolchain::installed()
+            .into_iter()
+            .filter(|toolchain| toolchain.version().pre.is_empty())
+            .collect::<Vec<crate::Toolchain>>();
+        toolchains.sort_by(|a, b| a.version().cmp(b.version()));
+        toolchains.dedup_by(|a, b| a.version() == b.version());
+
+        let (mut low, mut high) = (0, toolchains.len());
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let result = self.check(&toolchains[middle], &[]);
+
+            if verbosity > crate::Verbosity::Silent {
+                println!(
+                    "{}:  {}",
+                    toolchains[middle],
+                    if result.is_ok() { "passed" } else { "failed" }
+                );
+            }
+
+            if result.is_ok() {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+
+        let Some(lowest) = toolchains.get(low) else {
+            eprintln!("No locally installed stable toolchain compiles this workspace!");
+            return sysexits::ExitCode::DataErr;
+        };
+
+        println!("The lowest locally installed toolchain compiling this workspace is {lowest}.");
+
+        let mut code = sysexits::ExitCode::Ok;
+
+        let failed = low.checked_sub(1).map(|index| toolchains[index].version());
+
+        for package in self.metadata.members() {
+            let Some(declared) = package.rust_version() else {
+                continue;
+            };
+            let verdict =
+                Self::verdict(declared, lowest.version(), failed).unwrap_or_else(|verdict| {
+                    code = sysexits::ExitCode::DataErr;
+                    verdict
+                });
+
+            println!(
+                "{} declares `rust-version = \"{declared}\"` which {verdict}.",
+                package.name()
+            );
+        }
+
+        code
+    }
+
+    /// Configure a new verification of the minimum supported Rust versions.
+    #[must_use]
+    pub const fn new(metadata: std::sync::Arc<crate::Metadata>) -> Self {
+        Self { metadata }
+    }
+
+    /// Verify the declared minimum supported Rust versions.
+    ///
+    /// Members are grouped by their declared version such that each toolchain
+    /// is run once.  Versions without a locally installed toolchain are
+    /// reported as warnings, unless no version can be checked at all, which
+    /// lets the check be skipped.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let toolchains = crate::Toolchain::installed();
+        let mut versions = std::collections::BTreeMap::<&str, Vec<&str>>::new();
+        let mut checked = false;
+
+        for package in self.metadata.members() {
+            if let Some(version) = package.rust_version() {
+                versions.entry(version).or_default().push(package.name());
+            }
+        }
+
+        for (version, packages) in &versions {
+            let Some(toolchain) = toolchains
+                .iter()
+                .filter(|toolchain| toolchain.matches(version))
+                .min_by(|a, b| a.version().cmp(b.version()))
+            else {
+                findings.warning(format!(
+                    "toolchain {version} for {} is not installed",
+                    packages.join(", ")
+                ));
+                continue;
+            };
+
+            checked = true;
+
+            if let Err(error) = self.check(toolchain, packages) {
+                findings.error(format!(
+                    "`rust-version = \"{version}\"` of {} does not hold:  {error}",
+                    packages.join(", ")
+                ));
+            }
+        }
+
+        if !checked {
+            findings.skip(format!(
+                "MSRV check skipped:  toolchain {} not installed",
+                versions.keys().copied().collect::<Vec<&str>>().join(", ")
+            ));
+        }
+
+        findings
+    }
+
+    /// Judge the given declaration of a `rust-version` by the lowest toolchain
+    /// the workspace compiles with and the next older one, if any, which
+    /// failed.
+    ///
+    /// A declaration at or above the lowest toolchain holds.  A declaration
+    /// below it does not hold if the failed toolchain is at or above the
+    /// declared version.  Otherwise, the declared version was not tried such
+    /// that it cannot be verified.  The verdict is returned as error if the
+    /// declaration does not hold or is invalid.
+    fn verdict(
+        declared: &str,
+        lowest: &semver::Version,
+        failed: Option<&semver::Version>,
+    ) -> Result<&'static str, &'static str> {
+        // Cargo reads a `rust-version` without patch version as patch `0`.
+        let Ok(declared) = semver::Version::parse(&if declared.matches('.').count() == 1 {
+            format!("{declared}.0")
+        } else {
+            declared.to_string()
+        }) else {
+            return Err("is invalid");
+        };
+
+        if declared >= *lowest {
+            Ok("holds")
+        } else if failed.is_some_and(|failed| *failed >= declared) {
+            Err("does not hold")
+        } else {
+            Ok("cannot be verified with the installed toolchains")
+        }
+    }
+}
+
+impl std::fmt::Display for Msrv {
+    /// Implements the `Display` trait.
+    ///
+    /// A verification will be formatted like a command line naming the
+    /// declared versions.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "msrv")?;
+
+        for package in self.metadata.members() {
+            if let Some(version) = package.rust_version() {
+                write!(f, " {}@{version}", package.name())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Compare the given declaration to the given toolchain versions.
+    fn verdict(
+        declared: &str,
+        lowest: &str,
+        failed: Option<&str>,
+    ) -> Result<&'static str, &'static str> {
+        let failed = failed.map(|failed| semver::Version::parse(failed).unwrap());
+        super::Msrv::verdict(
+            declared,
+            &semver::Version::parse(lowest).unwrap(),
+            failed.as_ref(),
+        )
+    }
+
+    #[test]
+    fn verdict_cannot_be_verified() {
+        assert!(verdict("1.60", "1.65.0", None).is_ok_and(|verdict| verdict != "holds"));
+        assert!(verdict("1.60", "1.65.0", Some("1.58.0")).is_ok_and(|verdict| verdict != "holds"));
+        assert!(verdict("1.60", "1.65.0", Some("1.59.1")).is_ok_and(|verdict| verdict != "holds"));
+    }
+
+    #[test]
+    fn verdict_does_not_hold() {
+        assert_eq!(
+            verdict("1.60", "1.65.0", Some("1.60.0")),
+            Err("does not hold")
+        );
+        assert_eq!(
+            verdict("1.60", "1.65.0", Some("1.62.0")),
+            Err("does not hold")
+        );
+        assert_eq!(
+            verdict("1.60.1", "1.61.0", Some("1.60.1")),
+            Err("does not hold")
+        );
+        assert_eq!(
+            verdict("1.65", "1.65.1", Some("1.65.0")),
+            Err("does not hold")
+        );
+    }
+
+    #[test]
+    fn verdict_holds() {
+        assert_eq!(verdict("1.65", "1.65.0", Some("1.64.0")), Ok("holds"));
+        assert_eq!(verdict("1.65.1", "1.65.1", Some("1.65.0")), Ok("holds"));
+        assert_eq!(verdict("1.70.0", "1.65.0", None), Ok("holds"));
+    }
+
+    #[test]
+    fn verdict_is_invalid() {
+        assert_eq!(verdict("1", "1.65.0", None), Err("is invalid"));
+        assert_eq!(verdict("one", "1.65.0", None), Err("is invalid"));
+    }
+}
+
+/******************************************************************************/
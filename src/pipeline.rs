@@ -0,0 +1,743 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+//! The composition of the steps run by the binary executable.
+//!
+//! The steps are composed from the command line options, the configuration
+//! file and the metadata of the workspace.  The functions are only meant to be
+//! called by `crate::Application::main`.
+
+/// The steps running Cargo and its standard tools.
+fn cargo_steps(
+    config: &crate::Config,
+    lints: &[String],
+    verbosity: crate::Verbosity,
+) -> Vec<crate::ProcessStep> {
+    vec![
+        crate::ProcessStep::new(
+            "fix",
+            "cargo",
+            vec![
+                "clippy".into(),
+                "--fix".into(),
+                "--allow-dirty".into(),
+                "--allow-staged".into(),
+            ],
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        )
+        .mutating(),
+        crate::ProcessStep::new(
+            "fmt",
+            "cargo",
+            vec!["fmt".into()],
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        )
+        .mutating(),
+        crate::ProcessStep::new(
+            "fmt-check",
+            "cargo",
+            vec!["fmt".into(), "--check".into()],
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        ),
+        crate::ProcessStep::new(
+            "check",
+            "cargo",
+            vec!["check".into()],
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        )
+        .diagnostics(),
+        crate::ProcessStep::new(
+            "clippy",
+            "cargo",
+            [vec!["clippy".into(), "--".into()], lints.to_vec()].concat(),
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        )
+        .needs(&["check"])
+        .diagnostics(),
+        crate::ProcessStep::new(
+            "doc",
+            "cargo",
+            vec!["doc".into(), "--no-deps".into()],
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        )
+        .env("RUSTDOCFLAGS", &config.doc().rustdocflags())
+        .needs(&["check"])
+        .diagnostics(),
+    ]
+}
+
+/// The steps running the checks implemented by this crate.
+///
+/// The checks only wait for the steps they need, see `crate::Check::needs`.
+/// Each check is returned by its name, together with its step unless it is
+/// optional and not configured.
+fn checks(
+    args: &crate::CliOptions,
+    config: &crate::Config,
+    lints: &[String],
+    metadata: &std::sync::Arc<crate::Metadata>,
+) -> Vec<(String, Option<crate::ProcessStep>)> {
+    let shared = || std::sync::Arc::clone(metadata);
+    let workspace = metadata.workspace();
+    let mut checks = vec![
+        (
+            "unused".to_string(),
+            Some(crate::Check::Unused(crate::Unused::new(
+                config.unused(),
+                args.fix(),
+                shared(),
+            ))),
+            sysexits::ExitCode::DataErr,
+        ),
+        (
+            "duplicates".into(),
+            Some(crate::Check::Duplicates(crate::Duplicates::new(
+                config.duplicates(),
+                shared(),
+            ))),
+            sysexits::ExitCode::DataErr,
+        ),
+    ];
+
+    checks.push((
+        "features".into(),
+        config
+            .features()
+            .map(|features| crate::Check::Features(crate::Features::new(features, shared()))),
+        sysexits::ExitCode::DataErr,
+    ));
+
+    for target in config.cross().targets() {
+        checks.push((
+            target.clone(),
+            Some(crate::Check::Cross(crate::Cross::new(
+                target,
+                lints.to_vec(),
+            ))),
+            sysexits::ExitCode::DataErr,
+        ));
+    }
+
+    let changelog = workspace.root().join(config.changelog().file());
+
+    checks.push((
+        "changelog".into(),
+        changelog.is_file().then(|| {
+            crate::Check::Changelog(crate::Changelog::new(
+                config.changelog(),
+                &changelog,
+                shared(),
+            ))
+        }),
+        sysexits::ExitCode::DataErr,
+    ));
+    checks.push((
+        "headers".into(),
+        config.headers().map(|headers| {
+            crate::Check::Headers(crate::Headers::new(headers, args.fix(), &workspace))
+        }),
+        sysexits::ExitCode::DataErr,
+    ));
+    checks.push((
+        "licenses".into(),
+        config
+            .licenses()
+            .map(|policy| crate::Check::Licenses(crate::Licenses::new(policy, shared()))),
+        sysexits::ExitCode::DataErr,
+    ));
+    checks.push((
+        "audit".into(),
+        config.audit().database().map(|database| {
+            crate::Check::Audit(crate::Audit::new(
+                &workspace.root().join(database),
+                config.audit(),
+                shared(),
+            ))
+        }),
+        sysexits::ExitCode::DataErr,
+    ));
+    checks.push((
+        "msrv".into(),
+        metadata
+            .members()
+            .iter()
+            .any(|package| package.rust_version().is_some())
+            .then(|| crate::Check::Msrv(crate::Msrv::new(shared()))),
+        sysexits::ExitCode::DataErr,
+    ));
+    checks.push((
+        "semver".into(),
+        config.semver().map(|semver| {
+            crate::Check::Semver(crate::SemverCheck::new(semver.baseline(), shared()))
+        }),
+        sysexits::ExitCode::Protocol,
+    ));
+
+    checks
+        .into_iter()
+        .map(|(name, check, exit_code)| {
+            let step = check.map(|check| {
+                crate::ProcessStep::internal(&name, check, exit_code, args.verbosity())
+            });
+            (name, step)
+        })
+        .collect()
+}
+
+/// Compose the steps to run from the registry, the extensions and the tests.
+///
+/// The conditions of the steps are applied, as well.  If the configuration of
+/// a step is invalid, an explanation will be printed and `ExitCode::Config`
+/// will be returned.
+fn compose(
+    args: &crate::CliOptions,
+    config: &crate::Config,
+    profile: &crate::ProfileConfig,
+    metadata: &std::sync::Arc<crate::Metadata>,
+) -> Result<Vec<std::sync::Arc<dyn crate::Step>>, sysexits::ExitCode> {
+    let verbosity = args.verbosity();
+    let lints = lints();
+    let registry = cargo_steps(config, &lints, verbosity)
+        .into_iter()
+        .map(|step| (crate::Step::name(&step).to_string(), Some(step)))
+        .chain(checks(args, config, &lints, metadata))
+        .collect::<Vec<(String, Option<crate::ProcessStep>)>>();
+    let tests = tests(metadata, verbosity, args.test() || profile.test());
+    let mut known = registry
+        .iter()
+        .chain(&tests)
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<String>>();
+    let mut steps = registry
+        .into_iter()
+        .filter_map(|(_, step)| step)
+        .collect::<Vec<crate::ProcessStep>>();
+
+    extensions(
+        config,
+        metadata,
+        &mut known,
+        &mut steps,
+        selection(args, config, profile).as_deref(),
+        verbosity,
+    )?;
+
+    steps.extend(tests.into_iter().filter_map(|(_, step)| step));
+
+    conditions(config, metadata, &known, steps)
+}
+
+/// Skip the steps whose configured conditions do not hold.
+///
+/// Conditions need to refer to known steps, this is, to the steps of the
+/// registry, the extensions or the tests, even if they are not configured for
+/// this run.  Otherwise, an explanation will be printed and
+/// `ExitCode::Config` will be returned.
+fn conditions(
+    config: &crate::Config,
+    metadata: &crate::Metadata,
+    known: &[String],
+    steps: Vec<crate::ProcessStep>,
+) -> Result<Vec<std::sync::Arc<dyn crate::Step>>, sysexits::ExitCode> {
+    if let Some(name) = config
+        .conditional()
+        .into_iter()
+        .find(|name| !known.iter().any(|known| known == name))
+    {
+        eprintln!("There is no step named '{name}' to run under conditions!");
+        return Err(sysexits::ExitCode::Config);
+    }
+
+    Ok(steps
+        .into_iter()
+        .map(|step| {
+            match config
+                .when(crate::Step::name(&step))
+                .iter()
+                .find(|condition| !condition.holds(metadata))
+            {
+                Some(condition) => step.skip(format!("condition {condition} not met")),
+                None => step,
+            }
+        })
+        .map(|step| std::sync::Arc::new(step) as std::sync::Arc<dyn crate::Step>)
+        .collect())
+}
+
+/// Add the user-defined commands and the discovered plugins to the steps.
+///
+/// The names of the added steps are added to the given known ones.  A command
+/// must not reuse the name of another known step and must only need known
+/// steps.  Otherwise, an explanation will be printed and `ExitCode::Config`
+/// will be returned.  Plugins with names already taken are ignored instead
+/// since they are not configured explicitly.
+///
+/// The discovered plugins are only added if they are enabled or selected.  If
+/// no selection is given, such as for listing the steps, all of them are.
+/// Enabled plugins which are not installed are reported.
+fn extensions(
+    config: &crate::Config,
+    metadata: &std::sync::Arc<crate::Metadata>,
+    known: &mut Vec<String>,
+    steps: &mut Vec<crate::ProcessStep>,
+    selected: Option<&[&String]>,
+    verbosity: crate::Verbosity,
+) -> Result<(), sysexits::ExitCode> {
+    for command in config.commands() {
+        if known.iter().any(|name| name == command.name()) {
+            eprintln!("The step name '{}' is already taken!", command.name());
+            return Err(sysexits::ExitCode::Config);
+        }
+
+        let mut step = crate::ProcessStep::new(
+            command.name(),
+            command.program(),
+            command.args().to_vec(),
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        )
+        .current_dir(metadata.workspace().root())
+        .expect(command.expect().clone());
+
+        if command.mutating() {
+            step = step.mutating();
+        }
+
+        if let Some(needs) = command.needs() {
+            step = step.needs(&needs.iter().map(String::as_str).collect::<Vec<&str>>());
+        }
+
+        known.push(command.name().into());
+        steps.push(step);
+    }
+
+    let plugins = crate::Plugin::discover(metadata);
+
+    for name in config
+        .plugins()
+        .iter()
+        .filter(|name| !plugins.iter().any(|plugin| plugin.name() == *name))
+    {
+        eprintln!("The plugin '{name}' is not installed.");
+    }
+
+    for plugin in plugins {
+        if known.iter().any(|name| name == plugin.name()) {
+            eprintln!(
+                "Ignoring '{}' since the step name '{}' is already taken.",
+                plugin.path().display(),
+                plugin.name()
+            );
+            continue;
+        }
+
+        known.push(plugin.name().into());
+
+        if selected.is_some_and(|selected| !selected.iter().any(|name| *name == plugin.name())) {
+            continue;
+        }
+
+        steps.push(crate::ProcessStep::plugin(
+            plugin,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        ));
+    }
+
+    for command in config.commands() {
+        if let Some(need) = command
+            .needs()
+            .unwrap_or_default()
+            .iter()
+            .find(|need| !known.contains(need))
+        {
+            eprintln!(
+                "The command '{}' needs the unknown step '{need}'!",
+                command.name()
+            );
+            return Err(sysexits::ExitCode::Config);
+        }
+    }
+
+    Ok(())
+}
+
+/// The arguments to pass to Clippy.
+fn lints() -> Vec<String> {
+    [
+        "clippy::all",
+        "clippy::cargo",
+        "clippy::complexity",
+        "clippy::correctness",
+        "clippy::nursery",
+        "clippy::perf",
+        "clippy::pedantic",
+        "clippy::suspicious",
+        "clippy::style",
+    ]
+    .into_iter()
+    .flat_map(|lint| ["-D".into(), lint.into()])
+    // Duplicate dependency versions are reported by the duplicates step which
+    // explains them per dependent rather than failing the lints for them.
+    .chain(["-A".into(), "clippy::multiple_crate_versions".into()])
+    .collect()
+}
+
+/// Run the given application once per given toolchain.
+///
+/// The build artifacts of each toolchain are kept apart from the regular ones
+/// such that the toolchains do not invalidate each other's artifacts.
+fn matrix(
+    application: &crate::Application,
+    toolchains: &[String],
+    workspace: &crate::Workspace,
+    verbosity: crate::Verbosity,
+) -> sysexits::ExitCode {
+    let installed = crate::Toolchain::installed();
+    let mut matrix = crate::Matrix::new(verbosity);
+
+    for name in toolchains {
+        matrix = match installed.iter().find(|toolchain| toolchain.matches(name)) {
+            Some(toolchain) => matrix.column(
+                name,
+                application.toolchain(
+                    toolchain.name(),
+                    &workspace
+                        .target()
+                        .join("optimise")
+                        .join("toolchains")
+                        .join(toolchain.name()),
+                ),
+            ),
+            None => matrix.unavailable(name),
+        };
+    }
+
+    matrix.run()
+}
+
+/// Compose the steps for the given command line arguments and run them.
+///
+/// It takes care for the error event handling as well as the return status,
+/// see `crate::Application::main`.
+pub fn run(mut arguments: Vec<String>) -> sysexits::ExitCode {
+    // Cargo passes the name of the subcommand as first argument.
+    if arguments.get(1).map(String::as_str) == Some("optimise") {
+        arguments.remove(1);
+    }
+
+    let args = <crate::CliOptions as clap::Parser>::parse_from(arguments);
+
+    if args.license() {
+        crate::license();
+        return sysexits::ExitCode::Ok;
+    }
+
+    let metadata = match crate::Metadata::query() {
+        Ok(metadata) => std::sync::Arc::new(metadata),
+        Err(code) => return code,
+    };
+    let workspace = metadata.workspace();
+    let verbosity = args.verbosity();
+
+    if args.find_msrv() {
+        return crate::Msrv::new(metadata).find(verbosity);
+    }
+
+    let config = match crate::Config::load(
+        &args
+            .config()
+            .map_or_else(|| workspace.root().join("optimise.toml"), Into::into),
+    ) {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+
+    let profile = match config.profile(args.profile()) {
+        Ok(profile) => profile,
+        Err(error) => {
+            eprintln!("Invalid profile:  {error}!");
+            return sysexits::ExitCode::Config;
+        }
+    };
+
+    let steps = match compose(&args, &config, &profile, &metadata) {
+        Ok(steps) => steps,
+        Err(code) => return code,
+    };
+    let mut application =
+        crate::Application::new(steps, verbosity).metadata(std::sync::Arc::clone(&metadata));
+    let history = crate::History::new(&workspace);
+
+    match args.command() {
+        Some(crate::Command::List) => {
+            println!("{}", application.list());
+            return sysexits::ExitCode::Ok;
+        }
+        Some(crate::Command::History { runs }) => {
+            return trends(&history, args.toolchains(), *runs);
+        }
+        Some(crate::Command::Watch { .. }) | None => {}
+    }
+
+    application = match select(application, &args, &profile) {
+        Ok(application) => application,
+        Err(code) => return code,
+    };
+
+    if !args.no_cache() {
+        application = application.cache(crate::Cache::new(&workspace));
+    }
+
+    if let Some(file) = args.summary_markdown() {
+        application = application.summary(crate::Summary::new(file, &workspace));
+    }
+
+    if !args.toolchains().is_empty() {
+        return matrix(
+            &application.history(history),
+            args.toolchains(),
+            &workspace,
+            verbosity,
+        );
+    }
+
+    if let Some(crate::Command::Watch { debounce }) = args.command() {
+        crate::Watch::new(&workspace, std::time::Duration::from_millis(*debounce)).run(&application)
+    } else {
+        application.history(history).run()
+    }
+}
+
+/// Restrict the application to the steps selected by the command line and the
+/// profile.
+///
+/// Steps named on the command line need to exist.  The steps named by `--only`
+/// replace those of the profile and are run even if the profile skips them.
+/// Steps the profile restricts the run to but which are not configured are
+/// reported.  If none of them is configured, the run fails since it would not
+/// check anything the profile is meant for.
+fn select(
+    application: crate::Application,
+    args: &crate::CliOptions,
+    profile: &crate::ProfileConfig,
+) -> Result<crate::Application, sysexits::ExitCode> {
+    let steps = application.steps();
+
+    if let Some(name) = args
+        .only()
+        .iter()
+        .chain(args.skip())
+        .find(|name| !steps.contains(&name.as_str()))
+    {
+        eprintln!("There is no step named '{name}'!  See `cargo optimise list`.");
+        return Err(sysexits::ExitCode::Usage);
+    }
+
+    let only = if args.only().is_empty() {
+        let missing = profile
+            .only()
+            .iter()
+            .filter(|name| !steps.contains(&name.as_str()))
+            .collect::<Vec<&String>>();
+
+        for name in &missing {
+            eprintln!(
+                "The step '{name}' of the profile '{}' is not configured.",
+                args.profile()
+            );
+        }
+
+        if !missing.is_empty() && missing.len() == profile.only().len() {
+            eprintln!(
+                "None of the steps of the profile '{}' is configured!",
+                args.profile()
+            );
+            return Err(sysexits::ExitCode::Config);
+        }
+
+        profile.only()
+    } else {
+        args.only()
+    };
+    let skip = args
+        .skip()
+        .iter()
+        .chain(
+            profile
+                .skip()
+                .iter()
+                .filter(|name| !args.only().contains(name)),
+        )
+        .cloned()
+        .collect::<Vec<String>>();
+
+    Ok(application.select(only, &skip))
+}
+
+/// The names of the steps selected explicitly by the command line, the profile
+/// or the enabled plugins.
+///
+/// Since all steps are listed, there is no selection when listing them.
+fn selection<'a>(
+    args: &'a crate::CliOptions,
+    config: &'a crate::Config,
+    profile: &'a crate::ProfileConfig,
+) -> Option<Vec<&'a String>> {
+    (!matches!(args.command(), Some(crate::Command::List))).then(|| {
+        args.only()
+            .iter()
+            .chain(profile.only())
+            .chain(config.plugins())
+            .collect()
+    })
+}
+
+/// The steps running the tests of the workspace.
+///
+/// If `cargo nextest` is installed, it is preferred to `cargo test`.  Each
+/// step is returned by its name, together with the step if the tests are
+/// enabled and the step applies to the workspace.
+fn tests(
+    metadata: &crate::Metadata,
+    verbosity: crate::Verbosity,
+    enabled: bool,
+) -> Vec<(String, Option<crate::ProcessStep>)> {
+    if !enabled {
+        return vec![("test".into(), None), ("doctest".into(), None)];
+    }
+
+    let mut nextest = crate::Process::new(
+        "cargo",
+        vec!["nextest".into(), "--version".into()],
+        crate::Verbosity::Silent,
+    );
+
+    if !matches!(nextest.run(), sysexits::ExitCode::Ok) || !nextest.success() {
+        return vec![
+            (
+                "test".into(),
+                Some(
+                    crate::ProcessStep::new(
+                        "test",
+                        "cargo",
+                        vec!["test".into(), "--no-fail-fast".into()],
+                        None,
+                        sysexits::ExitCode::DataErr,
+                        verbosity,
+                    )
+                    .needs(&["check"])
+                    .tests(),
+                ),
+            ),
+            ("doctest".into(), None),
+        ];
+    }
+
+    let test = crate::ProcessStep::new(
+        "test",
+        "cargo",
+        vec![
+            "nextest".into(),
+            "run".into(),
+            "--no-fail-fast".into(),
+            "--no-tests=pass".into(),
+        ],
+        None,
+        sysexits::ExitCode::DataErr,
+        verbosity,
+    )
+    .needs(&["check"])
+    .tests();
+
+    // Documentation tests are not supported by `cargo nextest`.
+    let doctest = metadata.has_doctests().then(|| {
+        crate::ProcessStep::new(
+            "doctest",
+            "cargo",
+            vec!["test".into(), "--doc".into(), "--no-fail-fast".into()],
+            None,
+            sysexits::ExitCode::DataErr,
+            verbosity,
+        )
+        .needs(&["check"])
+        .tests()
+    });
+
+    vec![("test".into(), Some(test)), ("doctest".into(), doctest)]
+}
+
+/// Print the trends of the history over the given number of runs.
+///
+/// If toolchains are given, the trends of the runs with each of them are
+/// printed instead of those of the regular runs, see `matrix`.
+fn trends(history: &crate::History, toolchains: &[String], runs: usize) -> sysexits::ExitCode {
+    let print = |history: &crate::History| {
+        history.trends(runs).map_or_else(
+            |_| {
+                eprintln!("Failed to read the history!");
+                sysexits::ExitCode::IoErr
+            },
+            |trends| {
+                println!("{trends}");
+                sysexits::ExitCode::Ok
+            },
+        )
+    };
+
+    if toolchains.is_empty() {
+        return print(history);
+    }
+
+    let installed = crate::Toolchain::installed();
+    let mut code = sysexits::ExitCode::Ok;
+
+    for name in toolchains {
+        println!("\n==> {name} <==");
+
+        match installed.iter().find(|toolchain| toolchain.matches(name)) {
+            Some(toolchain) => {
+                let result = print(&history.toolchain(toolchain.name()));
+
+                if code.is_success() {
+                    code = result;
+                }
+            }
+            None => println!("The toolchain is not installed."),
+        }
+    }
+
+    code
+}
+
+/******************************************************************************/
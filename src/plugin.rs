@@ -0,0 +1,294 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// An external step provided by an executable on the `PATH`.
+///
+/// Like the subcommands of Cargo, executables named `optimise-step-<name>` are
+/// discovered as steps called `<name>`.  The discovered plugins are listed but
+/// only run if enabled in the configuration or selected explicitly.  A plugin is called with the argument
+/// `--message-format=json` and receives the context of the project as a JSON
+/// object on `stdin`:
+///
+/// * `changed_files`:  the files changed since the last commit, relative to
+///   the workspace root,
+/// * `packages`:  the members of the workspace with their `name`, `version`
+///   and `manifest_path`,
+/// * `verbosity`:  the verbosity of the run, and
+/// * `workspace_root`:  the directory containing the workspace manifest.
+///
+/// The plugin reports its diagnostics on `stdout` as compiler messages in the
+/// JSON format of Cargo such that they are reported like those of Clippy.  It
+/// fails by exiting non-zero.
+#[derive(Clone)]
+pub struct Plugin {
+    /// The information about the workspace to pass to the plugin.
+    metadata: std::sync::Arc<crate::Metadata>,
+
+    /// The name of the step.
+    name: String,
+
+    /// The executable of the plugin.
+    path: std::path::PathBuf,
+}
+
+impl Plugin {
+    /// The files of the workspace changed since the last commit.
+    ///
+    /// Both modified and untracked files are considered, the latter unless
+    /// they are ignored.  The paths are relative to the workspace root and
+    /// files outside of the workspace are omitted.  If the workspace is not maintained by Git, no files
+    /// will be returned.
+    fn changed_files(&self) -> Vec<String> {
+        let root = self
+            .metadata
+            .workspace()
+            .root()
+            .to_string_lossy()
+            .to_string();
+        let mut files = Vec::new();
+
+        for arguments in [
+            &["diff", "--name-only", "--relative", "HEAD"][..],
+            &["ls-files", "--others", "--exclude-standard"][..],
+        ] {
+            let mut git = crate::Process::new(
+                "git",
+                ["-C", &root]
+                    .iter()
+                    .chain(arguments)
+                    .map(ToString::to_string)
+                    .collect(),
+                crate::Verbosity::Silent,
+            );
+
+            if git.run().is_success() && git.success() {
+                files.extend(git.stdout().lines().map(String::from));
+            }
+        }
+
+        files
+    }
+
+    /// The context of the project to pass to the plugin as JSON.
+    #[must_use]
+    pub fn context(&self, verbosity: crate::Verbosity) -> String {
+        serde_json::json!({
+            "changed_files": self.changed_files(),
+            "packages": self
+                .metadata
+                .members()
+                .iter()
+                .map(|package| {
+                    serde_json::json!({
+                        "manifest_path": package.manifest_path(),
+                        "name": package.name(),
+                        "version": package.version(),
+                    })
+                })
+                .collect::<Vec<serde_json::Value>>(),
+            "verbosity": String::from(&verbosity),
+            "workspace_root": self.metadata.workspace().root(),
+        })
+        .to_string()
+    }
+
+    /// Discover the plugins on the `PATH`.
+    ///
+    /// If multiple executables provide the same step, the first one on the
+    /// `PATH` is used.  The plugins are ordered by their names.
+    #[must_use]
+    pub fn discover(metadata: &std::sync::Arc<crate::Metadata>) -> Vec<Self> {
+        Self::search(metadata, &std::env::var_os("PATH").unwrap_or_default())
+    }
+
+    /// Whether the given file can be executed.
+    #[cfg(unix)]
+    fn is_executable(path: &std::path::Path) -> bool {
+        std::fs::metadata(path).is_ok_and(|metadata| {
+            metadata.is_file()
+                && std::os::unix::fs::PermissionsExt::mode(&metadata.permissions()) & 0o111 != 0
+        })
+    }
+
+    /// Whether the given file can be executed.
+    #[cfg(not(unix))]
+    fn is_executable(path: &std::path::Path) -> bool {
+        path.is_file()
+    }
+
+    /// The name of the step.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The executable of the plugin.
+    #[must_use]
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Discover the plugins in the directories of the given search path.
+    fn search(metadata: &std::sync::Arc<crate::Metadata>, path: &std::ffi::OsStr) -> Vec<Self> {
+        let mut plugins = std::collections::BTreeMap::new();
+
+        for directory in std::env::split_paths(path) {
+            let Ok(entries) = std::fs::read_dir(directory) else {
+                continue;
+            };
+
+            for path in entries.flatten().map(|entry| entry.path()) {
+                let Some(name) = path
+                    .file_name()
+                    .and_then(std::ffi::OsStr::to_str)
+                    .and_then(|name| name.strip_prefix("optimise-step-"))
+                else {
+                    continue;
+                };
+
+                if !name.is_empty() && Self::is_executable(&path) {
+                    plugins.entry(name.to_string()).or_insert_with(|| Self {
+                        metadata: std::sync::Arc::clone(metadata),
+                        name: name.into(),
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+
+        plugins.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Create a fresh temporary directory.
+    fn directory(name: &str) -> std::path::PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("optimise-plugin-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    /// The information about a workspace with a single member in the given
+    /// directory.
+    fn metadata(root: &std::path::Path) -> std::sync::Arc<crate::Metadata> {
+        let metadata = serde_json::json!({
+            "packages": [{
+                "dependencies": [],
+                "features": {},
+                "id": "member 0.1.0",
+                "license": null,
+                "license_file": null,
+                "manifest_path": root.join("Cargo.toml"),
+                "name": "member",
+                "rust_version": null,
+                "source": null,
+                "targets": [],
+                "version": "0.1.0"
+            }, {
+                "dependencies": [],
+                "features": {},
+                "id": "dependency 1.0.0",
+                "license": null,
+                "license_file": null,
+                "manifest_path": "Cargo.toml",
+                "name": "dependency",
+                "rust_version": null,
+                "source": "registry",
+                "targets": [],
+                "version": "1.0.0"
+            }],
+            "resolve": null,
+            "target_directory": root.join("target"),
+            "workspace_members": ["member 0.1.0"],
+            "workspace_root": root
+        });
+
+        std::sync::Arc::new(serde_json::from_value(metadata).unwrap())
+    }
+
+    /// Create a file with the given permissions.
+    #[cfg(unix)]
+    fn file(path: &std::path::Path, mode: u32) {
+        std::fs::write(path, "").unwrap();
+        std::fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn context() {
+        let root = directory("context");
+        let plugin = super::Plugin {
+            metadata: metadata(&root),
+            name: "plugin".into(),
+            path: "optimise-step-plugin".into(),
+        };
+        let context =
+            serde_json::from_str::<serde_json::Value>(&plugin.context(crate::Verbosity::Chatty))
+                .unwrap();
+
+        assert_eq!(
+            context,
+            serde_json::json!({
+                "changed_files": [],
+                "packages": [{
+                    "manifest_path": root.join("Cargo.toml"),
+                    "name": "member",
+                    "version": "0.1.0"
+                }],
+                "verbosity": "chatty",
+                "workspace_root": root
+            })
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn search() {
+        let root = directory("search");
+        let first = root.join("first");
+        let second = root.join("second");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        file(&first.join("optimise-step-lint"), 0o755);
+        file(&first.join("optimise-step-"), 0o755);
+        file(&first.join("cargo-optimise"), 0o755);
+        file(&second.join("optimise-step-lint"), 0o755);
+        file(&second.join("optimise-step-format"), 0o755);
+        file(&second.join("optimise-step-data"), 0o644);
+
+        let path = std::env::join_paths([&first, &root.join("missing"), &second]).unwrap();
+        let plugins = super::Plugin::search(&metadata(&root), &path);
+
+        assert_eq!(
+            plugins
+                .iter()
+                .map(|plugin| (plugin.name(), plugin.path().to_path_buf()))
+                .collect::<Vec<_>>(),
+            [
+                ("format", second.join("optimise-step-format")),
+                ("lint", first.join("optimise-step-lint")),
+            ]
+        );
+    }
+}
+
+/******************************************************************************/
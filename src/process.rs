@@ -26,15 +26,36 @@ pub struct Process {
     /// The command line arguments to pass.
     arguments: Vec<String>,
 
+    /// The working directory of the process, if not the current one.
+    current_dir: Option<std::path::PathBuf>,
+
+    /// The diagnostics reported by the process, if they shall be collected.
+    diagnostics: Option<Vec<crate::Diagnostic>>,
+
+    /// The wall-clock time the execution took.
+    duration: std::time::Duration,
+
+    /// The environment variables to set.
+    environment: Vec<(String, String)>,
+
     /// The exit code after returning to the caller.
     exit: i32,
 
+    /// The expectations the process needs to meet in order to succeed.
+    expectation: crate::Expectation,
+
+    /// The input to write to `stdin`, if any.
+    input: Option<String>,
+
     /// The output written to `stderr` during the execution.
     stderr: String,
 
     /// The output written to `stdout` during the execution.
     stdout: String,
 
+    /// The resources consumed during the execution, if available.
+    usage: Option<crate::ResourceUsage>,
+
     /// The verbosity level.
     ///
     /// This field controls how much information shall be written to `stdout`:
@@ -46,17 +67,86 @@ pub struct Process {
 }
 
 impl Process {
+    /// Read the given output stream of a child process in the background.
+    ///
+    /// The streams of a child process need to be read concurrently in order to
+    /// avoid a deadlock when one of the pipes should be full.
+    fn read<R>(stream: Option<R>) -> std::thread::JoinHandle<std::io::Result<Vec<u8>>>
+    where
+        R: std::io::Read + Send + 'static,
+    {
+        std::thread::spawn(move || {
+            let mut buffer = Vec::new();
+
+            if let Some(mut stream) = stream {
+                std::io::Read::read_to_end(&mut stream, &mut buffer)?;
+            }
+
+            Ok(buffer)
+        })
+    }
+
+    /// Collect the diagnostics the process reports.
+    ///
+    /// The process is expected to be a Cargo subcommand which understands
+    /// `--message-format=json`, such as `cargo check` or `cargo clippy`.  The
+    /// option will be passed in front of the arguments for the compiler, if
+    /// any, but it will not be shown in the call line.
+    ///
+    /// Since the diagnostics are written to `stdout` as JSON messages then,
+    /// this instance will show them as they would have been written to
+    /// `stderr`, instead.
+    #[must_use]
+    pub fn collect_diagnostics(mut self) -> Self {
+        self.diagnostics = Some(Vec::new());
+        self
+    }
+
+    /// Run the process in the given working directory.
+    #[must_use]
+    pub fn current_dir(mut self, directory: &std::path::Path) -> Self {
+        self.current_dir = Some(directory.into());
+        self
+    }
+
+    /// The diagnostics reported by the process, if they were collected.
+    #[must_use]
+    pub fn diagnostics(&self) -> Option<&[crate::Diagnostic]> {
+        self.diagnostics.as_deref()
+    }
+
+    /// The wall-clock time the execution took.
+    #[must_use]
+    pub const fn duration(&self) -> std::time::Duration {
+        self.duration
+    }
+
+    /// Set the given environment variable for the process.
+    #[must_use]
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.environment.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set the expectations the process needs to meet in order to succeed.
+    #[must_use]
+    pub fn expect(mut self, expectation: crate::Expectation) -> Self {
+        self.expectation = expectation;
+        self
+    }
+
     /// The error message to show in case of an error.
     ///
-    /// If the called application should exit non-zero, the given message will
+    /// If the called application should not succeed, the given message will
     /// be written to `stderr`.  In case that no message should be given, the
-    /// error messages of the application will be shown instead.
+    /// error messages of the application will be shown instead, preceded by
+    /// the collected diagnostics, if any.
     #[must_use]
     pub fn failure(&self, error: Option<&str>) -> bool {
-        let message = match error {
-            Some(string) => format!("{string}\n"),
-            None => self.stderr.to_string(),
-        };
+        let message = error.map_or_else(
+            || self.rendered() + &self.stderr,
+            |string| format!("{string}\n"),
+        );
         let ret = !self.success();
 
         if ret {
@@ -117,6 +207,15 @@ impl Process {
         }
     }
 
+    /// Write the given input to `stdin` of the process.
+    ///
+    /// Without input, `stdin` is closed immediately.
+    #[must_use]
+    pub fn input(mut self, input: String) -> Self {
+        self.input = Some(input);
+        self
+    }
+
     /// Configure a new process.
     ///
     /// Since some information are unavailable before the process was executed,
@@ -134,18 +233,36 @@ impl Process {
         Self {
             application: application.into(),
             arguments: buffer,
+            current_dir: None,
+            diagnostics: None,
+            duration: std::time::Duration::ZERO,
+            environment: Vec::new(),
             exit: 0,
+            expectation: crate::Expectation::default(),
+            input: None,
             stderr: String::new(),
             stdout: String::new(),
+            usage: None,
             verbosity,
         }
     }
 
+    /// The collected diagnostics as they would have been written to `stderr`.
+    fn rendered(&self) -> String {
+        self.diagnostics
+            .iter()
+            .flatten()
+            .map(crate::Diagnostic::rendered)
+            .collect()
+    }
+
     /// Run the configured process.
     ///
     /// The configured process will be spawned by this method.  As soon as it
     /// returns, its exit code an any data written to both `stdout` and `stderr`
-    /// will be stored in this instance for later evaluation.
+    /// will be stored in this instance for later evaluation.  The same applies
+    /// to the wall-clock time the process took and, where available, to the
+    /// resources it consumed.
     ///
     /// Depending on the set verbosity level, some additional information might
     /// be written to `stdout`, as well:
@@ -175,29 +292,89 @@ impl Process {
             println!("{self}");
         }
 
+        let mut format = self.diagnostics.is_some();
+
+        if let Some(directory) = &self.current_dir {
+            process.current_dir(directory);
+        }
+
+        for (key, value) in &self.environment {
+            process.env(key, value);
+        }
+
         for argument in &self.arguments {
+            if format && argument == "--" {
+                process.arg("--message-format=json");
+                format = false;
+            }
+
             process.arg(argument);
         }
 
-        let output = match process.output() {
-            Ok(output) => output,
-            Err(_) => return sysexits::ExitCode::Unavailable,
+        if format {
+            process.arg("--message-format=json");
+        }
+
+        process
+            .stdin(if self.input.is_some() {
+                std::process::Stdio::piped()
+            } else {
+                std::process::Stdio::null()
+            })
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+
+        let start = std::time::Instant::now();
+        let Ok(mut child) = process.spawn() else {
+            return sysexits::ExitCode::Unavailable;
+        };
+        let stdin = child
+            .stdin
+            .take()
+            .zip(self.input.clone())
+            .map(|(mut stdin, input)| {
+                // The process may exit without reading its input.
+                std::thread::spawn(move || {
+                    let _ = std::io::Write::write_all(&mut stdin, input.as_bytes());
+                })
+            });
+        let stdout = Self::read(child.stdout.take());
+        let stderr = Self::read(child.stderr.take());
+        let Some((code, usage)) = Self::wait(&mut child) else {
+            return sysexits::ExitCode::Unavailable;
+        };
+
+        self.duration = start.elapsed();
+        self.usage = usage;
+
+        if let Some(stdin) = stdin {
+            let _ = stdin.join();
+        }
+
+        let (Ok(Ok(stdout)), Ok(Ok(stderr))) = (stdout.join(), stderr.join()) else {
+            return sysexits::ExitCode::DataErr;
         };
 
-        self.exit = match output.status.code() {
+        self.exit = match code {
             Some(number) => number,
             None => return sysexits::ExitCode::OsErr,
         };
-        self.stderr = match String::from_utf8(output.stderr) {
+        self.stderr = match String::from_utf8(stderr) {
             Ok(string) => string,
             Err(_) => return sysexits::ExitCode::DataErr,
         };
-        self.stdout = match String::from_utf8(output.stdout) {
+        self.stdout = match String::from_utf8(stdout) {
             Ok(string) => string,
             Err(_) => return sysexits::ExitCode::DataErr,
         };
 
-        if self.verbosity == crate::Verbosity::Chatty && !self.stdout.is_empty() {
+        if self.diagnostics.is_some() {
+            self.diagnostics = Some(crate::Diagnostic::parse(&self.stdout));
+
+            if self.verbosity == crate::Verbosity::Chatty {
+                eprint!("{}", self.rendered());
+            }
+        } else if self.verbosity == crate::Verbosity::Chatty && !self.stdout.is_empty() {
             println!("{}", self.stdout);
         }
 
@@ -208,13 +385,105 @@ impl Process {
         sysexits::ExitCode::Ok
     }
 
-    /// Whether the called application returned with code zero.
+    /// The output written to `stderr` during the execution.
+    #[must_use]
+    pub fn stderr(&self) -> &str {
+        &self.stderr
+    }
+
+    /// The output written to `stdout` during the execution.
+    #[must_use]
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    /// Whether the called application met the expectations.
     ///
     /// By convention, the exit code zero is assumed to indicate the success of
-    /// the called application.  Any other value is assumed to be a failure.
+    /// the called application.  Any other value is assumed to be a failure,
+    /// unless further exit codes or patterns for the output are expected, see
+    /// `expect`.
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.expectation
+            .holds(self.exit, &self.stdout, &self.stderr)
+    }
+
+    /// The resources consumed during the execution, if available.
     #[must_use]
-    pub const fn success(&self) -> bool {
-        self.exit == 0
+    pub const fn usage(&self) -> Option<crate::ResourceUsage> {
+        self.usage
+    }
+
+    /// Run the process and report whether it succeeded.
+    ///
+    /// # Errors
+    ///
+    /// If the process fails, the first error it wrote to `stderr`, such as the
+    /// first error reported by Cargo, will be returned.  If there is none, the
+    /// given fallback will be returned instead.
+    pub fn verify(&mut self, fallback: &str) -> Result<(), String> {
+        if self.run().is_success() && self.success() {
+            Ok(())
+        } else {
+            Err(self
+                .stderr
+                .lines()
+                .find(|line| line.starts_with("error"))
+                .unwrap_or(fallback)
+                .into())
+        }
+    }
+
+    /// Wait for the given child process to terminate.
+    ///
+    /// On Linux, the child will be waited for by `wait4` such that the
+    /// resources it consumed are reported, as well.  The returned exit code is
+    /// `None` if the child was terminated by a signal.  If waiting should fail,
+    /// `None` will be returned.
+    #[cfg(target_os = "linux")]
+    #[allow(clippy::needless_pass_by_ref_mut)] // The signature is shared by all platforms.
+    fn wait(
+        child: &mut std::process::Child,
+    ) -> Option<(Option<i32>, Option<crate::ResourceUsage>)> {
+        let pid = libc::pid_t::try_from(child.id()).ok()?;
+        let mut status = 0;
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+
+        loop {
+            // SAFETY:  `pid` names a child of this process which was not
+            // waited for, yet, and both pointers are valid for writes.
+            let result =
+                unsafe { libc::wait4(pid, std::ptr::addr_of_mut!(status), 0, usage.as_mut_ptr()) };
+
+            if result == pid {
+                break;
+            } else if std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted {
+                return None;
+            }
+        }
+
+        // SAFETY:  `wait4` succeeded and has therefore initialised `usage`.
+        let usage = unsafe { usage.assume_init() };
+        let code = if libc::WIFEXITED(status) {
+            Some(libc::WEXITSTATUS(status))
+        } else {
+            None
+        };
+
+        Some((code, Some(usage.into())))
+    }
+
+    /// Wait for the given child process to terminate.
+    ///
+    /// The returned exit code is `None` if the child was terminated by a
+    /// signal.  The consumed resources are not available on this platform.  If
+    /// waiting should fail, `None` will be returned.
+    #[cfg(not(target_os = "linux"))]
+    fn wait(
+        child: &mut std::process::Child,
+    ) -> Option<(Option<i32>, Option<crate::ResourceUsage>)> {
+        child.wait().ok().map(|status| (status.code(), None))
     }
 }
 
@@ -223,15 +492,20 @@ impl std::fmt::Display for Process {
     ///
     /// A `Process` instance will be formatted by naming the called application
     /// as well as all command line options passed to it in their order of
-    /// appearance, joined by one space character each.
+    /// appearance, joined by one space character each.  The environment
+    /// variables to set are prepended as assignments in shell syntax.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut buffer = self.application.to_string();
+        for (key, value) in &self.environment {
+            write!(f, "{key}='{value}' ")?;
+        }
+
+        write!(f, "{}", self.application)?;
 
         for argument in &self.arguments {
-            buffer.push_str(&format!(" {argument}"));
+            write!(f, " {argument}")?;
         }
 
-        write!(f, "{buffer}")
+        Ok(())
     }
 }
 
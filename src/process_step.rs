@@ -0,0 +1,476 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The settings of a single optimisation step backed by a process.
+///
+/// A step describes one process to be called by an `Application` together
+/// with the information how to react on its failure.  Alternatively, a step
+/// can run one of the built-in checks in-process.
+#[derive(Clone)]
+pub struct ProcessStep {
+    /// The process to be called.
+    application: String,
+
+    /// The command line arguments of the process to be called.
+    arguments: Vec<String>,
+
+    /// The check to run in-process instead of calling a process.
+    check: Option<crate::Check>,
+
+    /// The working directory of the process, if not the current one.
+    current_dir: Option<std::path::PathBuf>,
+
+    /// Whether the diagnostics of the process shall be collected.
+    diagnostics: bool,
+
+    /// The environment variables to set for the process.
+    environment: Vec<(String, String)>,
+
+    /// The error message to show in case the process should fail.
+    error_message: Option<String>,
+
+    /// The exit code to return by `main` in case the process should fail.
+    exit_code: sysexits::ExitCode,
+
+    /// The expectations the process needs to meet in order to succeed, if
+    /// other than the defaults.
+    expectation: Option<crate::Expectation>,
+
+    /// Whether the process modifies the sources of the project.
+    mutating: bool,
+
+    /// The name to refer to this step with.
+    name: String,
+
+    /// The names of the steps which need to succeed before this one, if
+    /// declared.
+    needs: Option<Vec<String>>,
+
+    /// The plugin providing this step, if any.
+    plugin: Option<crate::Plugin>,
+
+    /// The reason why this step is skipped, if so.
+    skipped: Option<String>,
+
+    /// Whether the process runs tests whose results shall be collected.
+    tests: bool,
+
+    /// The verbosity of the process.
+    verbosity: crate::Verbosity,
+}
+
+impl ProcessStep {
+    /// Run the process in the given working directory.
+    #[must_use]
+    pub fn current_dir(mut self, directory: &std::path::Path) -> Self {
+        self.current_dir = Some(directory.into());
+        self
+    }
+
+    /// Collect the diagnostics of this step.
+    ///
+    /// This requires the process to be a Cargo subcommand which understands
+    /// `--message-format=json`, such as `cargo check` or `cargo clippy`.
+    #[must_use]
+    pub const fn diagnostics(mut self) -> Self {
+        self.diagnostics = true;
+        self
+    }
+
+    /// Set the given environment variable for the process.
+    #[must_use]
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.environment.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set the expectations the process needs to meet in order to succeed.
+    #[must_use]
+    pub fn expect(mut self, expectation: crate::Expectation) -> Self {
+        self.expectation = Some(expectation);
+        self
+    }
+
+    /// Configure a new step running the given check in-process.
+    ///
+    /// The step is marked as mutating if the check modifies the workspace.  It
+    /// needs the steps the check needs, see `crate::Check::needs`.
+    #[must_use]
+    pub fn internal(
+        name: &str,
+        check: crate::Check,
+        exit_code: sysexits::ExitCode,
+        verbosity: crate::Verbosity,
+    ) -> Self {
+        Self {
+            mutating: check.is_mutating(),
+            needs: Some(check.needs().iter().map(ToString::to_string).collect()),
+            check: Some(check),
+            ..Self::new(name, "", Vec::new(), None, exit_code, verbosity)
+        }
+    }
+
+    /// Summarise the missing documentation among the given diagnostics.
+    ///
+    /// The diagnostics of the `missing_docs` lint are counted per kind of
+    /// item, such as functions or structs.  If there are none, no note will be
+    /// returned.
+    fn missing_docs(diagnostics: &[crate::Diagnostic]) -> Vec<String> {
+        let mut items = std::collections::BTreeMap::<&str, usize>::new();
+
+        for diagnostic in diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.lint() == Some("missing_docs"))
+        {
+            let item = diagnostic
+                .message()
+                .strip_prefix("missing documentation for ")
+                .unwrap_or("an item");
+            *items.entry(item).or_default() += 1;
+        }
+
+        if items.is_empty() {
+            return Vec::new();
+        }
+
+        vec![format!(
+            "missing documentation for {} items:  {}",
+            items.values().sum::<usize>(),
+            items
+                .iter()
+                .map(|(item, count)| format!("{item} ({count})"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )]
+    }
+
+    /// Mark this step as modifying the sources of the project.
+    #[must_use]
+    pub const fn mutating(mut self) -> Self {
+        self.mutating = true;
+        self
+    }
+
+    /// Declare the steps which need to succeed before this one.
+    ///
+    /// Steps which do not declare their needs wait for the preceding step.
+    /// Steps with declared needs may run in parallel to other steps once their
+    /// needs succeeded.
+    #[must_use]
+    pub fn needs(mut self, steps: &[&str]) -> Self {
+        self.needs = Some(steps.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    /// Configure a new step.
+    ///
+    /// Steps are assumed not to modify the sources of the project unless they
+    /// are marked to do so by calling `mutating`.
+    #[must_use]
+    pub fn new(
+        name: &str,
+        application: &str,
+        arguments: Vec<String>,
+        error_message: Option<String>,
+        exit_code: sysexits::ExitCode,
+        verbosity: crate::Verbosity,
+    ) -> Self {
+        Self {
+            application: application.into(),
+            arguments,
+            check: None,
+            current_dir: None,
+            diagnostics: false,
+            environment: Vec::new(),
+            error_message,
+            exit_code,
+            expectation: None,
+            mutating: false,
+            name: name.into(),
+            needs: None,
+            plugin: None,
+            skipped: None,
+            tests: false,
+            verbosity,
+        }
+    }
+
+    /// Configure a new step provided by the given plugin.
+    ///
+    /// The diagnostics the plugin reports are collected.  The context of the
+    /// project is passed to the plugin when the step is run.
+    #[must_use]
+    pub fn plugin(
+        plugin: crate::Plugin,
+        exit_code: sysexits::ExitCode,
+        verbosity: crate::Verbosity,
+    ) -> Self {
+        let step = Self::new(
+            plugin.name(),
+            &plugin.path().to_string_lossy(),
+            Vec::new(),
+            None,
+            exit_code,
+            verbosity,
+        )
+        .diagnostics();
+
+        Self {
+            plugin: Some(plugin),
+            ..step
+        }
+    }
+
+    /// Configure the process to be spawned by this step with the given
+    /// verbosity.
+    fn process(&self, verbosity: crate::Verbosity) -> crate::Process {
+        let mut process = crate::Process::new(&self.application, self.arguments.clone(), verbosity);
+
+        for (key, value) in &self.environment {
+            process = process.env(key, value);
+        }
+
+        if let Some(directory) = &self.current_dir {
+            process = process.current_dir(directory);
+        }
+
+        if self.diagnostics {
+            process = process.collect_diagnostics();
+        }
+
+        if let Some(expectation) = &self.expectation {
+            process = process.expect(expectation.clone());
+        }
+
+        process
+    }
+
+    /// Run the given check in-process.
+    ///
+    /// Findings letting the check fail will be written to `stderr`, all
+    /// findings will be stored as the notes of the returned outcome.  If the
+    /// check could not be run, the step is reported as skipped.
+    fn run_check(&self, check: &crate::Check, verbosity: crate::Verbosity) -> crate::StepOutcome {
+        if verbosity > crate::Verbosity::Silent {
+            println!("{self}");
+        }
+
+        let start = std::time::Instant::now();
+        let findings = check.run();
+        let duration = start.elapsed();
+        if let Some(reason) = findings.skipped() {
+            if verbosity > crate::Verbosity::Silent {
+                println!("{reason}");
+            }
+
+            return crate::StepOutcome::new(&self.name, crate::Status::Skipped)
+                .with_duration(duration)
+                .with_notes(vec![reason.into()]);
+        }
+
+        let status = if findings.is_failure() {
+            for error in findings.errors() {
+                eprintln!("{error}");
+            }
+
+            crate::Status::Failed(self.exit_code)
+        } else {
+            crate::Status::Passed
+        };
+
+        crate::StepOutcome::new(&self.name, status)
+            .with_duration(duration)
+            .with_notes(
+                findings
+                    .errors()
+                    .iter()
+                    .chain(findings.warnings())
+                    .cloned()
+                    .collect(),
+            )
+    }
+
+    /// Skip this step for the given reason instead of running it.
+    #[must_use]
+    pub fn skip(mut self, reason: String) -> Self {
+        self.skipped = Some(reason);
+        self
+    }
+
+    /// Collect the results of the tests this step runs.
+    ///
+    /// This requires the process to be a test runner whose output is
+    /// understood by `TestResults`, such as `cargo test` or `cargo nextest`.
+    #[must_use]
+    pub const fn tests(mut self) -> Self {
+        self.tests = true;
+        self
+    }
+}
+
+impl crate::Step for ProcessStep {
+    /// The settings identifying this step within the fingerprint cache.
+    ///
+    /// A check is identified by its settings, a process by its command line.
+    fn identity(&self) -> String {
+        self.check
+            .as_ref()
+            .map_or_else(|| self.to_string(), crate::Check::identity)
+    }
+
+    /// The files outside of the workspace the outcome of this step depends on.
+    ///
+    /// These are the inputs of the check or the executable of the plugin.
+    fn inputs(&self) -> Vec<std::path::PathBuf> {
+        let mut inputs = self
+            .check
+            .as_ref()
+            .map(crate::Check::inputs)
+            .unwrap_or_default();
+
+        if let Some(plugin) = &self.plugin {
+            inputs.push(plugin.path().into());
+        }
+
+        inputs
+    }
+
+    /// Whether this step modifies the sources of the project.
+    ///
+    /// Steps like `cargo fmt` or `cargo clippy --fix` write to the very files
+    /// they are run over.  This information is required by modes such as the
+    /// watch mode which would otherwise be triggered by these writes.
+    fn is_mutating(&self) -> bool {
+        self.mutating
+    }
+
+    /// Whether this step is skipped instead of being run.
+    fn is_skipped(&self) -> bool {
+        self.skipped.is_some()
+    }
+
+    /// The name to refer to this step with.
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the steps which need to succeed before this one, if
+    /// declared.
+    fn requirements(&self) -> Option<&[String]> {
+        self.needs.as_deref()
+    }
+
+    /// Run this step and handle all occurring errors.
+    ///
+    /// The configured process will be spawned and handled by the methods of
+    /// `Process`.  If it should not succeed, the resulting exit code will be
+    /// stored in the returned outcome such that it can be propagated to the
+    /// `main` function.
+    ///
+    /// If the diagnostics are collected, the missing documentation among them
+    /// will be summarised in the notes of the outcome.  If the step is to be
+    /// skipped, nothing will be run and the reason will be noted instead.
+    ///
+    /// The step is run with the lower one of its own verbosity and the one of
+    /// the given context such that steps configured to be quiet, such as
+    /// `metadata`, stay quiet while the whole run can be silenced.
+    fn run(&self, context: &crate::Context) -> crate::StepOutcome {
+        let verbosity = self.verbosity.min(context.verbosity());
+
+        if let Some(reason) = &self.skipped {
+            if verbosity > crate::Verbosity::Silent {
+                println!("{self} (skipped)");
+            }
+
+            return crate::StepOutcome::new(&self.name, crate::Status::Skipped)
+                .with_notes(vec![reason.clone()]);
+        }
+
+        if let Some(check) = &self.check {
+            return self.run_check(check, verbosity);
+        }
+
+        let mut process = self.process(verbosity);
+
+        if let Some(plugin) = &self.plugin {
+            process = process.input(plugin.context(verbosity));
+        }
+
+        let status = process
+            .handle(self.error_message.as_deref(), self.exit_code)
+            .map_or(crate::Status::Passed, crate::Status::Failed);
+
+        crate::StepOutcome::new(&self.name, status)
+            .with_diagnostics(process.diagnostics().map(<[crate::Diagnostic]>::to_vec))
+            .with_duration(process.duration())
+            .with_notes(
+                process
+                    .diagnostics()
+                    .map(Self::missing_docs)
+                    .unwrap_or_default(),
+            )
+            .with_tests(if self.tests {
+                crate::TestResults::parse(process.stdout(), process.stderr())
+            } else {
+                None
+            })
+            .with_usage(process.usage())
+    }
+
+    /// Run the Cargo process of this step with the given toolchain.
+    ///
+    /// The toolchain is selected by inserting `+<toolchain>` as the first
+    /// argument and the build artifacts are written to the given directory.
+    /// Steps running other applications or checks are not changed.
+    fn toolchain(
+        &self,
+        toolchain: &str,
+        target: &std::path::Path,
+    ) -> Option<std::sync::Arc<dyn crate::Step>> {
+        if self.check.is_some() || self.application != "cargo" {
+            return None;
+        }
+
+        let mut step = self
+            .clone()
+            .env("CARGO_TARGET_DIR", &target.to_string_lossy());
+        step.arguments.insert(0, format!("+{toolchain}"));
+        Some(std::sync::Arc::new(step))
+    }
+}
+
+impl std::fmt::Display for ProcessStep {
+    /// Implements the `Display` trait.
+    ///
+    /// A `ProcessStep` instance will be formatted just like the `Process` it
+    /// spawns or as description of the check it runs in-process.  Expectations
+    /// other than the defaults are appended to the process.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.check, &self.expectation) {
+            (Some(check), _) => write!(f, "{check} (in-process)"),
+            (None, Some(expectation)) => {
+                write!(f, "{} ({expectation})", self.process(self.verbosity))
+            }
+            (None, None) => write!(f, "{}", self.process(self.verbosity)),
+        }
+    }
+}
+
+/******************************************************************************/
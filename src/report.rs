@@ -0,0 +1,122 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The results of running an `Application`.
+pub struct Report {
+    /// The exit code to return by `main`.
+    code: sysexits::ExitCode,
+
+    /// The outcomes of the steps in their order of appearance.
+    outcomes: Vec<crate::StepOutcome>,
+}
+
+impl Report {
+    /// The exit code to return by `main`.
+    #[must_use]
+    pub const fn code(&self) -> sysexits::ExitCode {
+        self.code
+    }
+
+    /// Create a new report.
+    #[must_use]
+    pub const fn new(code: sysexits::ExitCode, outcomes: Vec<crate::StepOutcome>) -> Self {
+        Self { code, outcomes }
+    }
+
+    /// The outcomes of the steps in their order of appearance.
+    #[must_use]
+    pub fn outcomes(&self) -> &[crate::StepOutcome] {
+        &self.outcomes
+    }
+}
+
+impl std::fmt::Display for Report {
+    /// Implements the `Display` trait.
+    ///
+    /// A report will be formatted as a table with one line per step, naming the
+    /// step, its status and its duration.  If the consumed resources of at
+    /// least one step are known, the table will also list the CPU times and the
+    /// peak resident set size.  The table is followed by the test results of
+    /// the steps which ran tests, naming the failed tests, as well as the notes
+    /// of the steps.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let seconds = |duration: std::time::Duration| format!("{:.2}s", duration.as_secs_f64());
+        let width = self
+            .outcomes
+            .iter()
+            .map(|outcome| outcome.name().len())
+            .chain(std::iter::once("step".len()))
+            .max()
+            .unwrap_or_default();
+        let usage = self
+            .outcomes
+            .iter()
+            .any(|outcome| outcome.usage().is_some());
+
+        write!(f, "{:width$}  {:7}  {:>9}", "step", "status", "duration")?;
+
+        if usage {
+            write!(f, "  {:>9}  {:>9}  {:>10}", "user", "system", "peak RSS")?;
+        }
+
+        for outcome in &self.outcomes {
+            write!(
+                f,
+                "\n{:width$}  {:7}  {:>9}",
+                outcome.name(),
+                outcome.status().to_string(),
+                seconds(outcome.duration())
+            )?;
+
+            if let Some(resources) = outcome.usage() {
+                #[allow(clippy::cast_precision_loss)]
+                let peak = resources.peak_rss() as f64 / 1024.0;
+
+                write!(
+                    f,
+                    "  {:>9}  {:>9}  {:>6.1} MiB",
+                    seconds(resources.user()),
+                    seconds(resources.system()),
+                    peak
+                )?;
+            }
+        }
+
+        for outcome in &self.outcomes {
+            if let Some(tests) = outcome.tests() {
+                write!(f, "\n\n{}:  {tests}", outcome.name())?;
+
+                for failure in tests.failures() {
+                    write!(f, "\n  failed:  {failure}")?;
+                }
+            }
+        }
+
+        for outcome in &self.outcomes {
+            for note in outcome.notes() {
+                write!(f, "\n\n{}:  {note}", outcome.name())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,92 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The resources consumed by a process.
+///
+/// These information are only available on platforms which report them for
+/// each process waited for, such as Linux by means of `wait4`.  They include
+/// the resources consumed by all descendants the process waited for itself.
+#[derive(Clone, Copy)]
+pub struct ResourceUsage {
+    /// The peak resident set size in kibibytes.
+    peak_rss: u64,
+
+    /// The CPU time spent in kernel mode.
+    system: std::time::Duration,
+
+    /// The CPU time spent in user mode.
+    user: std::time::Duration,
+}
+
+impl ResourceUsage {
+    /// Create a new instance from the given measurements.
+    #[must_use]
+    pub const fn new(
+        peak_rss: u64,
+        system: std::time::Duration,
+        user: std::time::Duration,
+    ) -> Self {
+        Self {
+            peak_rss,
+            system,
+            user,
+        }
+    }
+
+    /// The peak resident set size in kibibytes.
+    #[must_use]
+    pub const fn peak_rss(&self) -> u64 {
+        self.peak_rss
+    }
+
+    /// The CPU time spent in kernel mode.
+    #[must_use]
+    pub const fn system(&self) -> std::time::Duration {
+        self.system
+    }
+
+    /// The CPU time spent in user mode.
+    #[must_use]
+    pub const fn user(&self) -> std::time::Duration {
+        self.user
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<libc::rusage> for ResourceUsage {
+    /// Implements the conversion from the `rusage` reported by `wait4`.
+    ///
+    /// Negative values, which are not expected to be reported at all, are
+    /// treated as zero.
+    fn from(usage: libc::rusage) -> Self {
+        let duration = |time: libc::timeval| {
+            std::time::Duration::from_secs(u64::try_from(time.tv_sec).unwrap_or_default())
+                + std::time::Duration::from_micros(u64::try_from(time.tv_usec).unwrap_or_default())
+        };
+
+        Self::new(
+            u64::try_from(usage.ru_maxrss).unwrap_or_default(),
+            duration(usage.ru_stime),
+            duration(usage.ru_utime),
+        )
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,853 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A check of the public API of the library crates against a baseline.
+///
+/// The public items of each library are read from the JSON output of
+/// `rustdoc`, for both the current sources and the baseline.  The baseline is
+/// either a Git reference, checked out into a temporary worktree, or a local
+/// `.crate` file.  Removed items and items whose signatures changed require a
+/// major version bump while added items require a minor one.  The items
+/// comprise the ones reachable from the crate root, including re-exports, as
+/// well as the fields, variants, methods and trait implementations of the
+/// public types.
+///
+/// Since the JSON output of `rustdoc` is unstable, it is enabled by setting
+/// `RUSTC_BOOTSTRAP=1` for the current toolchain.
+#[derive(Clone)]
+pub struct SemverCheck {
+    /// The Git reference or the `.crate` file to compare to.
+    baseline: String,
+
+    /// The commit the baseline refers to, if it is a Git reference.
+    commit: Option<String>,
+
+    /// The information about the workspace to check.
+    metadata: std::sync::Arc<crate::Metadata>,
+}
+
+/// The public API of a library, mapping the kinds and the paths of the items
+/// to their signatures.
+type Api = std::collections::BTreeMap<String, String>;
+
+/// The parts of the JSON output of `rustdoc` required for the comparison.
+#[derive(serde::Deserialize)]
+struct Documentation {
+    /// The version of the documented crate.
+    crate_version: Option<String>,
+
+    /// The items of the documented crate, identified by their IDs.
+    index: std::collections::HashMap<String, Item>,
+
+    /// The ID of the root module of the documented crate.
+    root: serde_json::Value,
+}
+
+/// An item of the documented crate.
+#[derive(serde::Deserialize)]
+struct Item {
+    /// The kind of the item together with its details, such as a signature.
+    inner: serde_json::Value,
+
+    /// The name of the item, unless it is a re-export or an implementation.
+    name: Option<String>,
+
+    /// The visibility of the item, such as `public`.
+    visibility: serde_json::Value,
+}
+
+/// The levels of version bumps.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    /// A bump for bug fixes only.
+    Patch,
+
+    /// A bump for backwards compatible additions.
+    Minor,
+
+    /// A bump for breaking changes.
+    Major,
+}
+
+impl Documentation {
+    /// The public API of the documented crate.
+    ///
+    /// The items are collected by walking the public items from the crate
+    /// root, following the re-exports, such that each item is listed with each
+    /// path it can be named with.
+    fn api(&self) -> Api {
+        let mut api = Api::new();
+
+        if let Some(root) = Item::id(&self.root) {
+            if let Some(name) = self.index.get(&root).and_then(|item| item.name.as_deref()) {
+                self.item(&root, name, &mut Vec::new(), &mut api);
+            }
+        }
+
+        api
+    }
+
+    /// Collect the trait implementations and the inherent members of a type.
+    ///
+    /// Trait implementations are listed by the trait they implement, except
+    /// for blanket implementations.  The public members of the inherent
+    /// implementations are listed below the path of the type.
+    fn implementations(
+        &self,
+        inner: &serde_json::Value,
+        path: &str,
+        modules: &mut Vec<String>,
+        api: &mut Api,
+    ) {
+        for id in Item::ids(&inner["impls"]) {
+            let Some(("impl", implementation)) = self.index.get(&id).and_then(Item::kind) else {
+                continue;
+            };
+
+            if !implementation["blanket_impl"].is_null() {
+                continue;
+            }
+
+            if let Some(name) = implementation["trait"]["path"].as_str() {
+                let mut arguments = implementation["trait"]["args"].clone();
+                SemverCheck::anonymise(&mut arguments);
+
+                api.insert(
+                    format!(
+                        "impl {}{name}{} for {path}",
+                        if implementation["is_negative"] == true {
+                            "!"
+                        } else {
+                            ""
+                        },
+                        if arguments.is_null() {
+                            String::new()
+                        } else {
+                            arguments.to_string()
+                        }
+                    ),
+                    String::new(),
+                );
+            } else {
+                self.members(&Item::ids(&implementation["items"]), path, modules, api);
+            }
+        }
+    }
+
+    /// Collect the given item with the given path together with its members.
+    ///
+    /// Modules which are already being walked are skipped such that re-exports
+    /// of a parent module do not cause an endless recursion.
+    fn item(&self, id: &str, path: &str, modules: &mut Vec<String>, api: &mut Api) {
+        let Some((kind, inner)) = self.index.get(id).and_then(Item::kind) else {
+            return;
+        };
+
+        if kind == "module" && modules.iter().any(|module| module == id) {
+            return;
+        }
+
+        api.insert(
+            format!("{} {path}", kind.replace('_', " ")),
+            SemverCheck::signature(inner),
+        );
+
+        match kind {
+            "enum" => {
+                self.members(&Item::ids(&inner["variants"]), path, modules, api);
+                self.implementations(inner, path, modules, api);
+            }
+            "module" => {
+                modules.push(id.into());
+                self.members(&Item::ids(&inner["items"]), path, modules, api);
+                modules.pop();
+            }
+            "struct" | "union" => {
+                self.members(&Item::fields(inner), path, modules, api);
+                self.implementations(inner, path, modules, api);
+            }
+            "trait" => self.members(&Item::ids(&inner["items"]), path, modules, api),
+            "variant" => self.members(&Item::fields(inner), path, modules, api),
+            _ => {}
+        }
+    }
+
+    /// Collect the public items with the given IDs below the given path.
+    fn members(&self, ids: &[String], path: &str, modules: &mut Vec<String>, api: &mut Api) {
+        for id in ids {
+            let Some(item) = self.index.get(id).filter(|item| item.is_public()) else {
+                continue;
+            };
+
+            match (item.kind(), &item.name) {
+                (Some(("use" | "import", inner)), _) => self.reexport(inner, path, modules, api),
+                (Some(_), Some(name)) => self.item(id, &format!("{path}::{name}"), modules, api),
+                _ => {}
+            }
+        }
+    }
+
+    /// Collect the items re-exported by the given `use` declaration.
+    ///
+    /// The members of modules and enumerations re-exported with a glob are
+    /// listed below the given path.  Re-exports of items of other crates are
+    /// listed with the path they refer to as signature.
+    fn reexport(
+        &self,
+        inner: &serde_json::Value,
+        path: &str,
+        modules: &mut Vec<String>,
+        api: &mut Api,
+    ) {
+        let target = Item::id(&inner["id"]).filter(|id| self.index.contains_key(id));
+
+        if inner["is_glob"] == true || inner["glob"] == true {
+            match target
+                .as_deref()
+                .and_then(|id| Some((id, self.index.get(id)?.kind()?)))
+            {
+                Some((id, ("module", module))) if !modules.iter().any(|other| other == id) => {
+                    modules.push(id.into());
+                    self.members(&Item::ids(&module["items"]), path, modules, api);
+                    modules.pop();
+                }
+                Some((_, ("enum", enumeration))) => {
+                    self.members(&Item::ids(&enumeration["variants"]), path, modules, api);
+                }
+                _ => {}
+            }
+        } else if let Some(name) = inner["name"].as_str() {
+            let path = format!("{path}::{name}");
+
+            match target {
+                Some(id) => self.item(&id, &path, modules, api),
+                None => {
+                    api.insert(
+                        format!("use {path}"),
+                        inner["source"].as_str().unwrap_or_default().into(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Item {
+    /// The IDs of the public fields of the given struct, union or variant.
+    fn fields(inner: &serde_json::Value) -> Vec<String> {
+        [
+            &inner["fields"],
+            &inner["kind"]["plain"]["fields"],
+            &inner["kind"]["struct"]["fields"],
+            &inner["kind"]["tuple"],
+        ]
+        .into_iter()
+        .flat_map(Self::ids)
+        .collect()
+    }
+
+    /// The given ID as key of the index.
+    ///
+    /// Depending on the version of the output format, IDs are numbers or
+    /// strings.  Stripped items are referred to by `null`.
+    fn id(value: &serde_json::Value) -> Option<String> {
+        match value {
+            serde_json::Value::Number(number) => Some(number.to_string()),
+            serde_json::Value::String(string) => Some(string.clone()),
+            _ => None,
+        }
+    }
+
+    /// The given list of IDs as keys of the index.
+    fn ids(value: &serde_json::Value) -> Vec<String> {
+        value
+            .as_array()
+            .map(|ids| ids.iter().filter_map(Self::id).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the item is visible wherever its parent is.
+    ///
+    /// This is the case for public items as well as for the members of
+    /// enumerations and traits.
+    fn is_public(&self) -> bool {
+        self.visibility == "public" || self.visibility == "default"
+    }
+
+    /// The kind of the item together with its details.
+    fn kind(&self) -> Option<(&str, &serde_json::Value)> {
+        self.inner
+            .as_object()
+            .and_then(|inner| inner.iter().next())
+            .map(|(kind, details)| (kind.as_str(), details))
+    }
+}
+
+impl SemverCheck {
+    /// Document the library of the given manifest and read the public API.
+    ///
+    /// The API is returned together with the version of the crate.
+    fn api(
+        manifest: &std::path::Path,
+        target: &std::path::Path,
+        name: &str,
+    ) -> Result<(String, Api), String> {
+        let mut process = crate::Process::new(
+            "cargo",
+            vec![
+                "rustdoc".into(),
+                "--lib".into(),
+                "--manifest-path".into(),
+                manifest.to_string_lossy().into(),
+                "--target-dir".into(),
+                target.to_string_lossy().into(),
+                "--".into(),
+                "-Z".into(),
+                "unstable-options".into(),
+                "--output-format".into(),
+                "json".into(),
+            ],
+            crate::Verbosity::Silent,
+        )
+        .env("RUSTC_BOOTSTRAP", "1");
+
+        if process.run().is_failure() || !process.success() {
+            return Err(format!("failed to document '{}'", manifest.display()));
+        }
+
+        let file = target
+            .join("doc")
+            .join(format!("{}.json", name.replace('-', "_")));
+        let documentation = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Documentation>(&json).ok())
+            .ok_or_else(|| format!("failed to understand '{}'", file.display()))?;
+
+        Ok((
+            documentation.crate_version.clone().unwrap_or_default(),
+            documentation.api(),
+        ))
+    }
+
+    /// Remove the IDs from the given details of an item.
+    ///
+    /// The IDs differ between the documentation of the current sources and the
+    /// baseline such that they need to be removed before comparing the
+    /// signatures.  The referenced items are still identified by their paths.
+    /// Bare IDs, such as the fields of a tuple struct, are replaced by `true`
+    /// such that their positions are kept, next to the stripped ones which are
+    /// `null`.
+    fn anonymise(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Array(values) => values.iter_mut().for_each(Self::anonymise),
+            serde_json::Value::Number(_) => *value = serde_json::Value::Bool(true),
+            serde_json::Value::Object(map) => {
+                map.remove("id");
+                map.values_mut().for_each(Self::anonymise);
+            }
+            _ => {}
+        }
+    }
+
+    /// The level of the bump between the given versions.
+    ///
+    /// Versions are considered incompatible according to the rules of Cargo,
+    /// see `Duplicates`.
+    fn bump(baseline: &semver::Version, current: &semver::Version) -> Level {
+        if crate::Duplicates::compatibility(baseline) != crate::Duplicates::compatibility(current) {
+            Level::Major
+        } else if baseline.major > 0 && baseline.minor != current.minor {
+            Level::Minor
+        } else {
+            Level::Patch
+        }
+    }
+
+    /// Check the given library against its baseline.
+    fn check(
+        package: &crate::Package,
+        baseline: &std::path::Path,
+        directory: &std::path::Path,
+        findings: &mut crate::Findings,
+    ) {
+        let name = package.name();
+        let current = match Self::api(package.manifest_path(), &directory.join("current"), name) {
+            Ok(api) => api,
+            Err(error) => return findings.error(error),
+        };
+
+        match Self::api(baseline, &directory.join("previous"), name) {
+            Ok(previous) => Self::compare(name, &previous, &current, findings),
+            Err(error) => findings.error(error),
+        }
+    }
+
+    /// Compare the given versions and APIs of a library.
+    ///
+    /// The changes to the API are reported as errors if the version was not
+    /// bumped accordingly, and as warnings, otherwise.
+    fn compare(
+        name: &str,
+        previous: &(String, Api),
+        current: &(String, Api),
+        findings: &mut crate::Findings,
+    ) {
+        let (Ok(current_version), Ok(previous_version)) = (
+            semver::Version::parse(&current.0),
+            semver::Version::parse(&previous.0),
+        ) else {
+            return findings.error(format!("{name}:  failed to compare the versions"));
+        };
+
+        let breaking = previous
+            .1
+            .keys()
+            .filter(|item| !current.1.contains_key(*item))
+            .map(|item| format!("removed {item}"))
+            .chain(previous.1.iter().filter_map(|(item, signature)| {
+                current
+                    .1
+                    .get(item)
+                    .filter(|current| *current != signature)
+                    .map(|_| format!("changed {item}"))
+            }))
+            .collect::<Vec<String>>();
+        let added = current
+            .1
+            .keys()
+            .filter(|item| !previous.1.contains_key(*item))
+            .count();
+        let required = if !breaking.is_empty() {
+            Level::Major
+        } else if added > 0 && previous_version.major > 0 {
+            Level::Minor
+        } else {
+            Level::Patch
+        };
+        let actual = Self::bump(&previous_version, &current_version);
+
+        if added > 0 {
+            findings.warning(format!(
+                "{name}:  {added} public items added since {previous_version}"
+            ));
+        }
+
+        if required > actual || (required > Level::Patch && current_version <= previous_version) {
+            findings.error(format!(
+                "{name}:  the changes since {previous_version} require a {} version bump, but the version is {current_version}",
+                match required {
+                    Level::Major => "major",
+                    Level::Minor => "minor",
+                    Level::Patch => "patch",
+                }
+            ));
+
+            for change in breaking {
+                findings.error(format!("{name}:  {change}"));
+            }
+        } else {
+            for change in breaking {
+                findings.warning(format!("{name}:  {change}"));
+            }
+        }
+    }
+
+    /// The files outside of the workspace the outcome of the check depends on.
+    ///
+    /// A `.crate` file as baseline is considered.  Git references are resolved
+    /// to their commit, instead, which the check is formatted with such that a
+    /// moved reference changes its fingerprint.
+    #[must_use]
+    pub fn inputs(&self) -> Vec<std::path::PathBuf> {
+        if self.is_crate_file() {
+            vec![self.metadata.workspace().root().join(&self.baseline)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Whether the baseline is a `.crate` file rather than a Git reference.
+    fn is_crate_file(&self) -> bool {
+        std::path::Path::new(&self.baseline)
+            .extension()
+            .is_some_and(|extension| extension == "crate")
+    }
+
+    /// Configure a new check against the given baseline.
+    #[must_use]
+    pub fn new(baseline: &str, metadata: std::sync::Arc<crate::Metadata>) -> Self {
+        let mut check = Self {
+            baseline: baseline.into(),
+            commit: None,
+            metadata,
+        };
+
+        check.commit = check.resolve();
+        check
+    }
+
+    /// Run `git` or `tar` with the given arguments and report whether it
+    /// succeeded.
+    fn execute(application: &str, arguments: Vec<String>) -> bool {
+        let mut process = crate::Process::new(application, arguments, crate::Verbosity::Silent);
+        process.run().is_success() && process.success()
+    }
+
+    /// Resolve the commit the baseline refers to, if it is a Git reference.
+    ///
+    /// The commit is resolved once when configuring the check such that the
+    /// baseline checked out and the one the check is identified by match.
+    fn resolve(&self) -> Option<String> {
+        let mut process = crate::Process::new(
+            "git",
+            vec![
+                "-C".into(),
+                self.metadata.workspace().root().to_string_lossy().into(),
+                "rev-parse".into(),
+                "--verify".into(),
+                "--quiet".into(),
+                format!("{}^{{commit}}", self.baseline),
+            ],
+            crate::Verbosity::Silent,
+        );
+
+        (!self.is_crate_file() && process.run().is_success() && process.success())
+            .then(|| process.stdout().trim().into())
+    }
+
+    /// Check the libraries of the workspace against the baseline.
+    ///
+    /// For a Git reference, all members with a library are checked, at the
+    /// same location within the worktree of the baseline.  For a `.crate`
+    /// file, only the package it contains is checked.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let workspace = self.metadata.workspace();
+        let directory = workspace.target().join("optimise").join("semver");
+        let checkout = directory.join("baseline");
+        let root = workspace.root().to_string_lossy().to_string();
+        let crate_file = self.is_crate_file();
+
+        let _ = std::fs::remove_dir_all(&checkout);
+
+        let prepared = if crate_file {
+            std::fs::create_dir_all(&checkout).is_ok()
+                && Self::execute(
+                    "tar",
+                    vec![
+                        "-xzf".into(),
+                        workspace
+                            .root()
+                            .join(&self.baseline)
+                            .to_string_lossy()
+                            .into(),
+                        "-C".into(),
+                        checkout.to_string_lossy().into(),
+                        "--strip-components=1".into(),
+                    ],
+                )
+        } else {
+            Self::execute(
+                "git",
+                vec![
+                    "-C".into(),
+                    root.clone(),
+                    "worktree".into(),
+                    "add".into(),
+                    "--detach".into(),
+                    "--force".into(),
+                    checkout.to_string_lossy().into(),
+                    self.commit.clone().unwrap_or_else(|| self.baseline.clone()),
+                ],
+            )
+        };
+
+        if !prepared {
+            findings.error(format!(
+                "failed to prepare the baseline `{}`",
+                self.baseline
+            ));
+            return findings;
+        }
+
+        for package in self.metadata.members() {
+            if !package
+                .targets()
+                .iter()
+                .any(|target| target.kind().iter().any(|kind| kind == "lib"))
+            {
+                continue;
+            }
+
+            let manifest = if crate_file {
+                if !std::path::Path::new(&self.baseline)
+                    .file_stem()
+                    .and_then(std::ffi::OsStr::to_str)
+                    .is_some_and(|stem| {
+                        stem.strip_prefix(package.name())
+                            .is_some_and(|rest| rest.starts_with('-'))
+                    })
+                {
+                    continue;
+                }
+
+                checkout.join("Cargo.toml")
+            } else {
+                match package.manifest_path().strip_prefix(workspace.root()) {
+                    Ok(relative) => checkout.join(relative),
+                    Err(_) => continue,
+                }
+            };
+
+            if manifest.is_file() {
+                Self::check(package, &manifest, &directory, &mut findings);
+            } else {
+                findings.warning(format!(
+                    "{}:  not part of the baseline `{}`",
+                    package.name(),
+                    self.baseline
+                ));
+            }
+        }
+
+        if !crate_file {
+            Self::execute(
+                "git",
+                vec![
+                    "-C".into(),
+                    root,
+                    "worktree".into(),
+                    "remove".into(),
+                    "--force".into(),
+                    checkout.to_string_lossy().into(),
+                ],
+            );
+        }
+
+        findings
+    }
+
+    /// The signature of an item from its details.
+    ///
+    /// The members of the item, which are listed on their own, as well as the
+    /// values of constants and statics are left out.  The remaining details
+    /// are anonymised, see `anonymise`.
+    fn signature(inner: &serde_json::Value) -> String {
+        let mut signature = inner.clone();
+
+        if let Some(details) = signature.as_object_mut() {
+            for key in [
+                "const",
+                "expr",
+                "fields",
+                "impls",
+                "implementations",
+                "is_stripped",
+                "items",
+            ] {
+                details.remove(key);
+            }
+        }
+
+        for kind in ["/kind/plain", "/kind/struct"] {
+            if let Some(kind) = signature
+                .pointer_mut(kind)
+                .and_then(serde_json::Value::as_object_mut)
+            {
+                kind.remove("fields");
+            }
+        }
+
+        Self::anonymise(&mut signature);
+        signature.to_string()
+    }
+}
+
+impl std::fmt::Display for SemverCheck {
+    /// Implements the `Display` trait.
+    ///
+    /// A check will be formatted like a command line naming the baseline.  A
+    /// Git reference is followed by the commit it referred to when the check
+    /// was configured.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "semver --baseline {}", self.baseline)?;
+
+        self.commit
+            .as_ref()
+            .map_or(Ok(()), |commit| write!(f, " ({commit})"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Compare the given versions and APIs, returning the errors and warnings.
+    fn compare(
+        previous: (&str, &[(&str, &str)]),
+        current: (&str, &[(&str, &str)]),
+    ) -> (Vec<String>, Vec<String>) {
+        let api = |(version, items): (&str, &[(&str, &str)])| {
+            (
+                version.to_string(),
+                items
+                    .iter()
+                    .map(|(item, signature)| ((*item).to_string(), (*signature).to_string()))
+                    .collect(),
+            )
+        };
+        let mut findings = crate::Findings::default();
+
+        super::SemverCheck::compare("lib", &api(previous), &api(current), &mut findings);
+        (findings.errors().to_vec(), findings.warnings().to_vec())
+    }
+
+    /// Parse the given versions and determine the level of the bump.
+    fn bump(baseline: &str, current: &str) -> super::Level {
+        super::SemverCheck::bump(
+            &semver::Version::parse(baseline).unwrap(),
+            &semver::Version::parse(current).unwrap(),
+        )
+    }
+
+    #[test]
+    fn anonymise() {
+        let mut value = serde_json::json!({
+            "id": 3,
+            "kind": {"tuple": [4, null]},
+            "type": {"resolved_path": {"path": "Foo", "id": 5, "args": null}},
+        });
+
+        super::SemverCheck::anonymise(&mut value);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "kind": {"tuple": [true, null]},
+                "type": {"resolved_path": {"path": "Foo", "args": null}},
+            })
+        );
+    }
+
+    #[test]
+    fn api_reexports() {
+        let documentation = serde_json::from_value::<super::Documentation>(serde_json::json!({
+            "crate_version": "1.0.0",
+            "root": 0,
+            "index": {
+                "0": {"name": "lib", "visibility": "public", "inner": {"module": {"is_crate": true, "items": [1, 2, 3, 9]}}},
+                "1": {"name": null, "visibility": "public", "inner": {"use": {"source": "inner::S", "name": "S", "id": 4, "is_glob": false}}},
+                "2": {"name": null, "visibility": "public", "inner": {"use": {"source": "inner::*", "name": "inner", "id": 10, "is_glob": true}}},
+                "3": {"name": null, "visibility": "public", "inner": {"use": {"source": "std::fmt::Debug", "name": "Debug", "id": 99, "is_glob": false}}},
+                "4": {"name": "S", "visibility": "public", "inner": {"struct": {"kind": {"plain": {"fields": [5], "has_stripped_fields": true}}, "generics": {}, "impls": [6, 8]}}},
+                "5": {"name": "a", "visibility": "public", "inner": {"struct_field": {"primitive": "u8"}}},
+                "6": {"name": null, "visibility": "default", "inner": {"impl": {"trait": null, "items": [7], "blanket_impl": null}}},
+                "7": {"name": "m", "visibility": "public", "inner": {"function": {"sig": {"inputs": [], "output": null}}}},
+                "8": {"name": null, "visibility": "default", "inner": {"impl": {"trait": {"path": "Clone", "id": 98, "args": null}, "items": [], "blanket_impl": null, "is_negative": false}}},
+                "9": {"name": "hidden", "visibility": "crate", "inner": {"function": {"sig": {"inputs": [], "output": null}}}},
+                "10": {"name": "inner", "visibility": "public", "inner": {"module": {"is_crate": false, "items": [11, 2]}}},
+                "11": {"name": "E", "visibility": "public", "inner": {"enum": {"variants": [12], "impls": []}}},
+                "12": {"name": "A", "visibility": "default", "inner": {"variant": {"kind": "plain", "discriminant": null}}},
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            documentation.api().into_keys().collect::<Vec<String>>(),
+            [
+                "enum lib::E",
+                "function lib::S::m",
+                "impl Clone for lib::S",
+                "module lib",
+                "struct field lib::S::a",
+                "struct lib::S",
+                "use lib::Debug",
+                "variant lib::E::A",
+            ]
+        );
+    }
+
+    #[test]
+    fn bump_major() {
+        assert!(bump("1.2.3", "2.0.0") == super::Level::Major);
+        assert!(bump("0.2.3", "0.3.0") == super::Level::Major);
+        assert!(bump("0.0.1", "0.0.2") == super::Level::Major);
+    }
+
+    #[test]
+    fn bump_minor() {
+        assert!(bump("1.2.3", "1.3.0") == super::Level::Minor);
+    }
+
+    #[test]
+    fn bump_patch() {
+        assert!(bump("1.2.3", "1.2.4") == super::Level::Patch);
+        assert!(bump("0.2.3", "0.2.4") == super::Level::Patch);
+    }
+
+    #[test]
+    fn compare_added() {
+        let previous = ("1.0.0", &[("function lib::f", "()")][..]);
+        let added = &[("function lib::f", "()"), ("function lib::g", "()")][..];
+
+        let (errors, warnings) = compare(previous, ("1.0.1", added));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(warnings, ["lib:  1 public items added since 1.0.0"]);
+
+        let (errors, _) = compare(previous, ("1.1.0", added));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn compare_changed() {
+        let previous = ("1.0.0", &[("function lib::f", "()")][..]);
+        let changed = &[("function lib::f", "(u8)")][..];
+
+        let (errors, _) = compare(previous, ("1.1.0", changed));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1], "lib:  changed function lib::f");
+
+        let (errors, warnings) = compare(previous, ("2.0.0", changed));
+        assert!(errors.is_empty());
+        assert_eq!(warnings, ["lib:  changed function lib::f"]);
+    }
+
+    #[test]
+    fn compare_removed() {
+        let previous = ("0.1.0", &[("function lib::f", "()")][..]);
+
+        let (errors, _) = compare(previous, ("0.1.1", &[]));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1], "lib:  removed function lib::f");
+
+        let (errors, _) = compare(previous, ("0.2.0", &[]));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn compare_unchanged() {
+        let api = &[("function lib::f", "()")][..];
+
+        assert_eq!(
+            compare(("1.0.0", api), ("1.0.0", api)),
+            (Vec::new(), Vec::new())
+        );
+    }
+}
+
+/******************************************************************************/
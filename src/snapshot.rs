@@ -0,0 +1,193 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The contents of the files of a workspace at a certain point in time.
+///
+/// A snapshot is used to determine the changes the mutating steps applied to
+/// the sources of the project.
+pub struct Snapshot {
+    /// The contents of the files, identified by their paths.
+    files: std::collections::BTreeMap<std::path::PathBuf, String>,
+
+    /// The workspace the snapshot was taken of.
+    workspace: crate::Workspace,
+}
+
+impl Snapshot {
+    /// Compare the snapshot with the current state of the workspace.
+    ///
+    /// Files which are not valid UTF-8 are not considered.  The numbers of
+    /// inserted and deleted lines are those of a shortest edit script turning
+    /// the old version into the new one, just like `diff` would count them.
+    #[must_use]
+    pub fn changes(&self) -> Vec<FileChange> {
+        let current = Self::take(&self.workspace);
+        let mut paths = self
+            .files
+            .keys()
+            .chain(current.files.keys())
+            .collect::<Vec<&std::path::PathBuf>>();
+
+        paths.sort();
+        paths.dedup();
+
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let old = self.files.get(path).map_or("", String::as_str);
+                let new = current.files.get(path).map_or("", String::as_str);
+
+                if old == new {
+                    return None;
+                }
+
+                let (insertions, deletions) = Self::count(old, new);
+
+                Some(FileChange {
+                    deletions,
+                    insertions,
+                    path: path
+                        .strip_prefix(self.workspace.root())
+                        .unwrap_or(path)
+                        .to_path_buf(),
+                })
+            })
+            .collect()
+    }
+
+    /// Count the inserted and deleted lines.
+    ///
+    /// The lines both versions start and end with are removed first.  The
+    /// remaining lines are compared in order using the greedy algorithm by
+    /// Myers which finds the length of a shortest edit script in a time
+    /// proportional to the size of the files and the number of changes.
+    fn count(old: &str, new: &str) -> (usize, usize) {
+        let old = old.lines().collect::<Vec<&str>>();
+        let new = new.lines().collect::<Vec<&str>>();
+        let prefix = old
+            .iter()
+            .zip(&new)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let old = &old[prefix..old.len() - suffix];
+        let new = &new[prefix..new.len() - suffix];
+        let (n, m) = (old.len(), new.len());
+        let offset = n + m + 1;
+        let mut furthest = vec![0; 2 * offset + 1];
+
+        for edits in 0..=n + m {
+            for diagonal in (0..=edits).map(|step| offset + 2 * step - edits) {
+                let mut x = if diagonal == offset - edits
+                    || (diagonal != offset + edits
+                        && furthest[diagonal - 1] < furthest[diagonal + 1])
+                {
+                    furthest[diagonal + 1]
+                } else {
+                    furthest[diagonal - 1] + 1
+                };
+                let mut y = (x + offset).saturating_sub(diagonal);
+
+                while old.get(x).is_some_and(|line| new.get(y) == Some(line)) {
+                    x += 1;
+                    y += 1;
+                }
+
+                furthest[diagonal] = x;
+
+                if x >= n && y >= m {
+                    return ((edits + m - n) / 2, (edits + n - m) / 2);
+                }
+            }
+        }
+
+        (m, n)
+    }
+
+    /// Take a snapshot of the given workspace.
+    #[must_use]
+    pub fn take(workspace: &crate::Workspace) -> Self {
+        Self {
+            files: workspace
+                .files()
+                .into_iter()
+                .filter_map(|path| Some((path.clone(), std::fs::read_to_string(path).ok()?)))
+                .collect(),
+            workspace: workspace.clone(),
+        }
+    }
+}
+
+/// The change of a single file between a snapshot and the current state.
+pub struct FileChange {
+    /// The number of deleted lines.
+    deletions: usize,
+
+    /// The number of inserted lines.
+    insertions: usize,
+
+    /// The path of the file, relative to the workspace root.
+    path: std::path::PathBuf,
+}
+
+impl FileChange {
+    /// The number of deleted lines.
+    #[must_use]
+    pub const fn deletions(&self) -> usize {
+        self.deletions
+    }
+
+    /// The number of inserted lines.
+    #[must_use]
+    pub const fn insertions(&self) -> usize {
+        self.insertions
+    }
+
+    /// The path of the file, relative to the workspace root.
+    #[must_use]
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn count_moved_lines() {
+        assert_eq!(super::Snapshot::count("a\nb\nc", "b\nc\na"), (1, 1));
+    }
+
+    #[test]
+    fn count_one_changed_region() {
+        assert_eq!(super::Snapshot::count("a\nb\nc\nd", "a\nx\ny\nd"), (2, 2));
+    }
+
+    #[test]
+    fn count_unchanged() {
+        assert_eq!(super::Snapshot::count("a\nb", "a\nb"), (0, 0));
+    }
+}
+
+/******************************************************************************/
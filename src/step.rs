@@ -0,0 +1,98 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A single optimisation step run by an `Application`.
+///
+/// Besides the built-in `ProcessStep`, which spawns a process or runs one of
+/// the built-in checks, steps can be implemented in Rust by the users of this
+/// library.  The `Display` representation of a step is shown when it is run.
+/// The identity of a step, which defaults to the `Display` representation,
+/// identifies it in the fingerprint cache, such that it should name all of its
+/// settings.
+///
+/// Steps are shared among the threads running them in parallel.
+pub trait Step: std::fmt::Display + Send + Sync {
+    /// The settings identifying this step within the fingerprint cache.
+    ///
+    /// Steps whose `Display` representation does not name all of their
+    /// settings should override this.  By default, it is the `Display`
+    /// representation.
+    fn identity(&self) -> String {
+        self.to_string()
+    }
+
+    /// The files outside of the workspace the outcome of this step depends on.
+    ///
+    /// These files are considered by the fingerprint cache in addition to the
+    /// files of the workspace.  By default, there are none.
+    fn inputs(&self) -> Vec<std::path::PathBuf> {
+        Vec::new()
+    }
+
+    /// Whether this step modifies the sources of the project.
+    ///
+    /// Mutating steps never run in parallel to other steps and are omitted by
+    /// the watch mode.  By default, steps are assumed not to do so.
+    fn is_mutating(&self) -> bool {
+        false
+    }
+
+    /// Whether this step is skipped instead of being run.
+    ///
+    /// Skipped steps bypass the fingerprint cache.  By default, steps are run.
+    fn is_skipped(&self) -> bool {
+        false
+    }
+
+    /// The name to refer to this step with.
+    ///
+    /// The name is used to select the step as well as in the reports.  It
+    /// should be unique among the steps of an `Application`.
+    fn name(&self) -> &str;
+
+    /// The names of the steps which need to succeed before this one, if
+    /// declared.
+    ///
+    /// By default, a step waits for the preceding one.
+    fn requirements(&self) -> Option<&[String]> {
+        None
+    }
+
+    /// Run this step within the given context.
+    ///
+    /// The returned outcome names the status of the step and, if the step
+    /// failed, the exit code to propagate to the `main` function.
+    fn run(&self, context: &crate::Context) -> crate::StepOutcome;
+
+    /// Derive a copy of this step running with the given toolchain.
+    ///
+    /// The build artifacts of the copy are expected to be written to the given
+    /// directory.  Steps which do not depend on the toolchain return `None`,
+    /// which is the default, and are not run per toolchain.
+    fn toolchain(
+        &self,
+        _toolchain: &str,
+        _target: &std::path::Path,
+    ) -> Option<std::sync::Arc<dyn Step>> {
+        None
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,181 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The outcome of a single optimisation step.
+#[derive(Clone)]
+pub struct StepOutcome {
+    /// The diagnostics reported by the step, if they were collected.
+    diagnostics: Option<Vec<crate::Diagnostic>>,
+
+    /// The wall-clock time the step took.
+    duration: std::time::Duration,
+
+    /// The name of the step.
+    name: String,
+
+    /// Further findings of the step to be shown in the summaries.
+    notes: Vec<String>,
+
+    /// Whether the step succeeded.
+    status: Status,
+
+    /// The results of the tests run by the step, if they were collected.
+    tests: Option<crate::TestResults>,
+
+    /// The resources consumed by the step, if available.
+    usage: Option<crate::ResourceUsage>,
+}
+
+impl StepOutcome {
+    /// The diagnostics reported by the step, if they were collected.
+    #[must_use]
+    pub(crate) fn diagnostics(&self) -> Option<&[crate::Diagnostic]> {
+        self.diagnostics.as_deref()
+    }
+
+    /// The wall-clock time the step took.
+    #[must_use]
+    pub const fn duration(&self) -> std::time::Duration {
+        self.duration
+    }
+
+    /// The name of the step.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Create a new outcome for the step with the given name.
+    ///
+    /// The further details of the outcome are empty and can be set by the
+    /// respective `with_*` methods.
+    #[must_use]
+    pub fn new(name: &str, status: Status) -> Self {
+        Self {
+            diagnostics: None,
+            duration: std::time::Duration::ZERO,
+            name: name.into(),
+            notes: Vec::new(),
+            status,
+            tests: None,
+            usage: None,
+        }
+    }
+
+    /// Further findings of the step to be shown in the summaries.
+    #[must_use]
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// Whether the step succeeded.
+    #[must_use]
+    pub const fn status(&self) -> Status {
+        self.status
+    }
+
+    /// The results of the tests run by the step, if they were collected.
+    #[must_use]
+    pub(crate) const fn tests(&self) -> Option<&crate::TestResults> {
+        self.tests.as_ref()
+    }
+
+    /// The resources consumed by the step, if available.
+    #[must_use]
+    pub(crate) const fn usage(&self) -> Option<crate::ResourceUsage> {
+        self.usage
+    }
+
+    /// Set the diagnostics reported by the step.
+    #[must_use]
+    pub(crate) fn with_diagnostics(mut self, diagnostics: Option<Vec<crate::Diagnostic>>) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    /// Set the wall-clock time the step took.
+    #[must_use]
+    pub const fn with_duration(mut self, duration: std::time::Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Set the further findings of the step.
+    #[must_use]
+    pub fn with_notes(mut self, notes: Vec<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Set the results of the tests run by the step.
+    #[must_use]
+    pub(crate) fn with_tests(mut self, tests: Option<crate::TestResults>) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Set the resources consumed by the step.
+    #[must_use]
+    pub(crate) const fn with_usage(mut self, usage: Option<crate::ResourceUsage>) -> Self {
+        self.usage = usage;
+        self
+    }
+}
+
+/// The possible states a step can end in.
+#[derive(Clone, Copy)]
+pub enum Status {
+    /// The step succeeded before with identical inputs and was not run.
+    Cached,
+
+    /// The step failed with the given exit code.
+    Failed(sysexits::ExitCode),
+
+    /// The step succeeded.
+    Passed,
+
+    /// The step was not run.
+    ///
+    /// This is the case if a step it depends on failed, if its conditions do
+    /// not hold, or if it cannot be run in this environment, for instance due
+    /// to a missing target, toolchain, or external tool.
+    Skipped,
+}
+
+impl std::fmt::Display for Status {
+    /// Implements the `Display` trait.
+    ///
+    /// A status will be represented by its lowercase name.  The exit code of a
+    /// failed step is not shown.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Cached => "cached",
+                Self::Failed(_) => "failed",
+                Self::Passed => "passed",
+                Self::Skipped => "skipped",
+            }
+        )
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,318 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+// The trait is required by the `write!` macro for the summary.
+use std::fmt::Write as _;
+
+/// A concise Markdown summary of a run.
+///
+/// The summary is meant to be posted as comment to a pull request or to be
+/// shown as summary of a CI job.  It names the overall status, the outcomes of
+/// the steps, the most frequent lints and the changes the mutating steps
+/// applied to the sources.  The runs with another toolchain, see
+/// `--toolchains`, are summarised one by one, naming the toolchain.
+pub struct Summary {
+    /// The file to append the summary to.
+    file: std::path::PathBuf,
+
+    /// The toolchain the summarised run used, unless it is the default one.
+    toolchain: Option<String>,
+
+    /// The workspace the summary is about.
+    workspace: crate::Workspace,
+}
+
+impl Summary {
+    /// The maximum number of lints to list.
+    const LINTS: usize = 10;
+
+    /// The maximum number of distinct locations to list per lint.
+    const LOCATIONS: usize = 3;
+
+    /// Escape the given text to fit into a single cell of a Markdown table.
+    ///
+    /// Pipes would end the cell early and line breaks would end the row, so
+    /// the former are escaped and the latter are rendered as HTML breaks.
+    fn cell(text: &str) -> String {
+        text.replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace(['\n', '\r'], "<br>")
+    }
+
+    /// The link to the given location within the sources.
+    ///
+    /// When run on GitHub Actions, the link will point to the file in the
+    /// repository at the commit the run was triggered for.  Otherwise, the link
+    /// will be relative to the workspace root.
+    fn link(file: &str, line: Option<u64>) -> String {
+        let anchor = line.map_or_else(String::new, |line| format!("#L{line}"));
+        let label = line.map_or_else(|| file.to_string(), |line| format!("{file}:{line}"));
+        let base = match (
+            std::env::var("GITHUB_SERVER_URL"),
+            std::env::var("GITHUB_REPOSITORY"),
+            std::env::var("GITHUB_SHA"),
+        ) {
+            (Ok(server), Ok(repository), Ok(sha)) => {
+                format!("{server}/{repository}/blob/{sha}/")
+            }
+            _ => String::new(),
+        };
+
+        format!("[{label}]({base}{file}{anchor})")
+    }
+
+    /// Configure a summary to be appended to the given file.
+    ///
+    /// The file will be appended to such that it can be the job summary file
+    /// of a CI system, such as `$GITHUB_STEP_SUMMARY`.
+    #[must_use]
+    pub fn new(file: &std::path::Path, workspace: &crate::Workspace) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            toolchain: None,
+            workspace: workspace.clone(),
+        }
+    }
+
+    /// Render the summary of the given report and the given changes.
+    #[must_use]
+    pub fn render(&self, report: &crate::Report, changes: &[crate::FileChange]) -> String {
+        let title = self.toolchain.as_ref().map_or_else(
+            || "cargo optimise".to_string(),
+            |toolchain| format!("cargo optimise with `{toolchain}`"),
+        );
+        let mut markdown = if report.code().is_success() {
+            format!("## ✅ {title}: passed\n")
+        } else {
+            format!("## ❌ {title}: failed with exit code {}\n", report.code())
+        };
+
+        markdown.push_str("\n| Step | Status | Duration |\n| :--- | :--- | ---: |\n");
+
+        for outcome in report.outcomes() {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | {:.2}s |",
+                Self::cell(outcome.name()),
+                Self::cell(&outcome.status().to_string()),
+                outcome.duration().as_secs_f64()
+            );
+        }
+
+        Self::render_tests(&mut markdown, report);
+        Self::render_notes(&mut markdown, report);
+        Self::render_lints(&mut markdown, report);
+        Self::render_changes(&mut markdown, changes);
+        markdown
+    }
+
+    /// Render the changes the mutating steps applied to the sources.
+    fn render_changes(markdown: &mut String, changes: &[crate::FileChange]) {
+        let insertions = changes
+            .iter()
+            .map(crate::FileChange::insertions)
+            .sum::<usize>();
+        let deletions = changes
+            .iter()
+            .map(crate::FileChange::deletions)
+            .sum::<usize>();
+
+        markdown.push_str("\n### Auto-fix changes\n\n");
+
+        if changes.is_empty() {
+            markdown.push_str("No files were changed.\n");
+            return;
+        }
+
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        let _ = writeln!(
+            markdown,
+            "{} file{} changed, {insertions} insertion{}(+), {deletions} deletion{}(-)\n",
+            changes.len(),
+            plural(changes.len()),
+            plural(insertions),
+            plural(deletions)
+        );
+        markdown.push_str("| File | Insertions | Deletions |\n| :--- | ---: | ---: |\n");
+
+        for change in changes {
+            let _ = writeln!(
+                markdown,
+                "| `{}` | {} | {} |",
+                Self::cell(&change.path().display().to_string()),
+                change.insertions(),
+                change.deletions()
+            );
+        }
+    }
+
+    /// Render the most frequent lints together with some of their locations.
+    ///
+    /// Diagnostics reported by more than one step, such as the compiler lints
+    /// reported by both `cargo check` and `cargo clippy`, are counted once.
+    fn render_lints(markdown: &mut String, report: &crate::Report) {
+        let mut diagnostics = Vec::<&crate::Diagnostic>::new();
+
+        for diagnostic in report
+            .outcomes()
+            .iter()
+            .filter_map(crate::StepOutcome::diagnostics)
+            .flatten()
+        {
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        let mut lints = std::collections::BTreeMap::<&str, Vec<&crate::Diagnostic>>::new();
+
+        for diagnostic in diagnostics {
+            if let Some(lint) = diagnostic.lint() {
+                lints.entry(lint).or_default().push(diagnostic);
+            }
+        }
+
+        let mut lints = lints
+            .into_iter()
+            .collect::<Vec<(&str, Vec<&crate::Diagnostic>)>>();
+        lints.sort_by(|(a, a_diagnostics), (b, b_diagnostics)| {
+            b_diagnostics.len().cmp(&a_diagnostics.len()).then(a.cmp(b))
+        });
+
+        if lints.is_empty() {
+            return;
+        }
+
+        markdown
+            .push_str("\n### Top lints\n\n| Lint | Count | Locations |\n| :--- | ---: | :--- |\n");
+
+        for (lint, diagnostics) in lints.iter().take(Self::LINTS) {
+            let mut locations = Vec::<String>::new();
+
+            for diagnostic in diagnostics {
+                if let Some(file) = diagnostic.file() {
+                    let link = Self::link(file, diagnostic.line());
+
+                    if !locations.contains(&link) {
+                        locations.push(link);
+                    }
+                }
+            }
+
+            let more = locations.len().saturating_sub(Self::LOCATIONS);
+            locations.truncate(Self::LOCATIONS);
+
+            if more > 0 {
+                locations.push(format!("+{more} more"));
+            }
+
+            let _ = writeln!(
+                markdown,
+                "| `{}` | {} | {} |",
+                Self::cell(lint),
+                diagnostics.len(),
+                Self::cell(&locations.join(", "))
+            );
+        }
+    }
+
+    /// Render the notes of the steps, if any.
+    fn render_notes(markdown: &mut String, report: &crate::Report) {
+        if report
+            .outcomes()
+            .iter()
+            .all(|outcome| outcome.notes().is_empty())
+        {
+            return;
+        }
+
+        markdown.push_str("\n### Notes\n\n");
+
+        for outcome in report.outcomes() {
+            for note in outcome.notes() {
+                let _ = writeln!(markdown, "- **{}**:  {note}", outcome.name());
+            }
+        }
+    }
+
+    /// Render the results of the steps which ran tests.
+    fn render_tests(markdown: &mut String, report: &crate::Report) {
+        for outcome in report.outcomes() {
+            if let Some(tests) = outcome.tests() {
+                let _ = writeln!(markdown, "\n### Tests (`{}`)\n\n{tests}", outcome.name());
+
+                if !tests.failures().is_empty() {
+                    markdown.push_str("\nFailed tests:\n\n");
+
+                    for failure in tests.failures() {
+                        let _ = writeln!(markdown, "* `{failure}`");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Take a snapshot of the workspace to compare the final state with.
+    #[must_use]
+    pub fn snapshot(&self) -> crate::Snapshot {
+        crate::Snapshot::take(&self.workspace)
+    }
+
+    /// Derive the summary of the runs with the given toolchain.
+    #[must_use]
+    pub fn toolchain(&self, toolchain: &str) -> Self {
+        Self {
+            file: self.file.clone(),
+            toolchain: Some(toolchain.into()),
+            workspace: self.workspace.clone(),
+        }
+    }
+
+    /// Append the summary of the given report to the configured file.
+    ///
+    /// The changes are determined by comparing the given snapshot with the
+    /// current state of the workspace.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be written, the underlying I/O error will be
+    /// returned.
+    pub fn write(&self, report: &crate::Report, snapshot: &crate::Snapshot) -> std::io::Result<()> {
+        std::io::Write::write_all(
+            &mut std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&self.file)?,
+            self.render(report, &snapshot.changes()).as_bytes(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn cell() {
+        assert_eq!(super::Summary::cell("fmt"), "fmt");
+        assert_eq!(super::Summary::cell("a | b"), "a \\| b");
+        assert_eq!(super::Summary::cell("a\nb\r\nc"), "a<br>b<br>c");
+    }
+}
+
+/******************************************************************************/
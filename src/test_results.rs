@@ -0,0 +1,189 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The results of running the tests of a project.
+#[derive(Clone, Default)]
+pub struct TestResults {
+    /// The number of failed tests.
+    failed: usize,
+
+    /// The names of the failed tests.
+    failures: Vec<String>,
+
+    /// The number of ignored or skipped tests.
+    ignored: usize,
+
+    /// The number of passed tests.
+    passed: usize,
+}
+
+impl TestResults {
+    /// Read the number following the given label from the given text.
+    ///
+    /// The text is expected to contain the number in front of the label, such
+    /// as `3 passed`.  The numbers of all occurrences of the label are summed.
+    fn count(text: &str, label: &str) -> usize {
+        text.split([',', ';', ':'])
+            .filter_map(|part| part.trim().strip_suffix(label))
+            .filter_map(|number| number.split_whitespace().last()?.parse::<usize>().ok())
+            .sum()
+    }
+
+    /// The names of the failed tests.
+    #[must_use]
+    pub fn failures(&self) -> &[String] {
+        &self.failures
+    }
+
+    /// Read the test results from the output of the test harness.
+    ///
+    /// Both the output of the default test harness, as written to `stdout` by
+    /// `cargo test`, and the output of `cargo nextest run`, as written to
+    /// `stderr`, are understood.  If no results can be found, `None` will be
+    /// returned.
+    #[must_use]
+    pub fn parse(stdout: &str, stderr: &str) -> Option<Self> {
+        let mut found = false;
+        let mut results = Self::default();
+
+        for line in stdout.lines() {
+            if let Some(summary) = line.strip_prefix("test result: ") {
+                found = true;
+                results.passed += Self::count(summary, " passed");
+                results.failed += Self::count(summary, " failed");
+                results.ignored += Self::count(summary, " ignored");
+            } else if let Some(name) = line
+                .strip_prefix("test ")
+                .and_then(|line| line.strip_suffix(" ... FAILED"))
+            {
+                results.failure(name);
+            }
+        }
+
+        for line in stderr.lines().map(str::trim) {
+            if line.starts_with("Summary [") && line.contains(" tests run: ") {
+                found = true;
+                results.passed += Self::count(line, " passed");
+                results.failed += Self::count(line, " failed");
+                results.ignored += Self::count(line, " skipped");
+            } else if let Some((_, name)) = line
+                .strip_prefix("FAIL [")
+                .and_then(|line| line.split_once("] "))
+            {
+                results.failure(name);
+            }
+        }
+
+        found.then_some(results)
+    }
+
+    /// Remember the given name of a failed test, unless already known.
+    fn failure(&mut self, name: &str) {
+        if !self.failures.iter().any(|failure| failure == name) {
+            self.failures.push(name.into());
+        }
+    }
+}
+
+impl std::fmt::Display for TestResults {
+    /// Implements the `Display` trait.
+    ///
+    /// The results will be formatted as the numbers of passed, failed and
+    /// ignored tests.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} ignored",
+            self.passed, self.failed, self.ignored
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// The output of `cargo test` for a library with a failing test and the
+    /// documentation tests.
+    const CARGO_TEST: &str = "
+running 3 tests
+test tests::a ... ok
+test tests::b ... FAILED
+test tests::c ... ignored
+
+failures:
+
+---- tests::b stdout ----
+assertion failed
+
+failures:
+    tests::b
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+running 2 tests
+test src/lib.rs - f (line 3) ... ok
+test src/lib.rs - g (line 9) ... ok
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.31s
+";
+
+    /// The output of `cargo nextest run` with a failing test.
+    const NEXTEST: &str = "
+    Starting 3 tests across 2 binaries (1 test skipped)
+        PASS [   0.004s] example tests::a
+        FAIL [   0.005s] example tests::b
+     Summary [   0.006s] 2 tests run: 1 passed, 1 failed, 1 skipped
+        FAIL [   0.005s] example tests::b
+";
+
+    #[test]
+    fn parse_failed() {
+        let results = super::TestResults::parse(CARGO_TEST, "").unwrap();
+
+        assert_eq!(results.to_string(), "3 passed, 1 failed, 1 ignored");
+        assert_eq!(results.failures(), ["tests::b"]);
+    }
+
+    #[test]
+    fn parse_nextest() {
+        let results = super::TestResults::parse("", NEXTEST).unwrap();
+
+        assert_eq!(results.to_string(), "1 passed, 1 failed, 1 ignored");
+        assert_eq!(results.failures(), ["example tests::b"]);
+    }
+
+    #[test]
+    fn parse_none() {
+        assert!(super::TestResults::parse("running 0 tests", "error: could not compile").is_none());
+    }
+
+    #[test]
+    fn parse_passed() {
+        let results = super::TestResults::parse(
+            "test result: ok. 4 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out",
+            "",
+        )
+        .unwrap();
+
+        assert_eq!(results.to_string(), "4 passed, 0 failed, 0 ignored");
+        assert!(results.failures().is_empty());
+    }
+}
+
+/******************************************************************************/
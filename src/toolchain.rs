@@ -0,0 +1,175 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A Rust toolchain installed by `rustup`.
+#[derive(Clone)]
+pub struct Toolchain {
+    /// The name of the toolchain, such as `stable-x86_64-unknown-linux-gnu`.
+    name: String,
+
+    /// The version of the compiler of the toolchain.
+    version: semver::Version,
+}
+
+impl Toolchain {
+    /// Query the toolchains installed by `rustup`.
+    ///
+    /// The version of each toolchain is determined by asking its compiler.
+    /// Toolchains whose version cannot be determined are omitted.  If `rustup`
+    /// should not be available, no toolchains will be returned.
+    #[must_use]
+    pub fn installed() -> Vec<Self> {
+        let mut rustup = crate::Process::new(
+            "rustup",
+            vec!["toolchain".into(), "list".into()],
+            crate::Verbosity::Silent,
+        );
+
+        if rustup.run().is_failure() || !rustup.success() {
+            return Vec::new();
+        }
+
+        rustup
+            .stdout()
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter_map(|name| {
+                let mut rustc = crate::Process::new(
+                    "rustc",
+                    vec![format!("+{name}"), "--version".into()],
+                    crate::Verbosity::Silent,
+                );
+
+                if rustc.run().is_failure() || !rustc.success() {
+                    return None;
+                }
+
+                Some(Self {
+                    name: name.into(),
+                    version: semver::Version::parse(rustc.stdout().split_whitespace().nth(1)?)
+                        .ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the given toolchain specification refers to this toolchain.
+    ///
+    /// A specification is either the name of the toolchain, with or without
+    /// the host triple, or a version, such as `1.70` or `1.70.0`.
+    #[must_use]
+    pub fn matches(&self, specification: &str) -> bool {
+        if self.name == specification
+            || self.name.strip_prefix(specification).is_some_and(|rest| {
+                rest.starts_with('-') && !rest[1..].starts_with(|c: char| c.is_ascii_digit())
+            })
+        {
+            return true;
+        }
+
+        let parts = specification
+            .split('.')
+            .map(str::parse::<u64>)
+            .collect::<Result<Vec<u64>, _>>()
+            .unwrap_or_default();
+
+        self.version.pre.is_empty()
+            && match parts.as_slice() {
+                [major, minor] => self.version.major == *major && self.version.minor == *minor,
+                [major, minor, patch] => {
+                    self.version.major == *major
+                        && self.version.minor == *minor
+                        && self.version.patch == *patch
+                }
+                _ => false,
+            }
+    }
+
+    /// The name of the toolchain, such as `stable-x86_64-unknown-linux-gnu`.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The version of the compiler of the toolchain.
+    #[must_use]
+    pub const fn version(&self) -> &semver::Version {
+        &self.version
+    }
+}
+
+impl std::fmt::Display for Toolchain {
+    /// Implements the `Display` trait.
+    ///
+    /// A toolchain will be formatted by its name and its version.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Create a toolchain with the given name and version.
+    fn toolchain(name: &str, version: &str) -> super::Toolchain {
+        super::Toolchain {
+            name: name.into(),
+            version: semver::Version::parse(version).unwrap(),
+        }
+    }
+
+    #[test]
+    fn matches_name() {
+        let stable = toolchain("stable-x86_64-unknown-linux-gnu", "1.75.0");
+
+        assert!(stable.matches("stable-x86_64-unknown-linux-gnu"));
+        assert!(stable.matches("stable"));
+        assert!(!stable.matches("stab"));
+        assert!(!stable.matches("beta"));
+    }
+
+    #[test]
+    fn matches_version() {
+        let pinned = toolchain("1.75.0-x86_64-unknown-linux-gnu", "1.75.0");
+
+        assert!(pinned.matches("1.75"));
+        assert!(pinned.matches("1.75.0"));
+        assert!(!pinned.matches("1.75.1"));
+        assert!(!pinned.matches("1.7"));
+        assert!(!pinned.matches("1"));
+    }
+
+    #[test]
+    fn matches_versioned_name() {
+        let pinned = toolchain("1.75-x86_64-unknown-linux-gnu", "1.75.0");
+
+        assert!(pinned.matches("1.75-x86_64-unknown-linux-gnu"));
+        assert!(!pinned.matches("1.7"));
+    }
+
+    #[test]
+    fn matches_no_prerelease() {
+        let nightly = toolchain("nightly-x86_64-unknown-linux-gnu", "1.77.0-nightly");
+
+        assert!(nightly.matches("nightly"));
+        assert!(!nightly.matches("1.77"));
+    }
+}
+
+/******************************************************************************/
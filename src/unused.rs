@@ -0,0 +1,450 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// A detection of the dependencies which are declared but never used.
+///
+/// By default, the sources of each workspace member are searched for the
+/// identifiers of its dependencies, similar to `cargo machete`.  In the precise
+/// mode, the compiler reports the unused dependencies of each target by the
+/// `unused_crate_dependencies` lint instead.  A dependency is then considered
+/// unused if all targets it is available to report it.
+///
+/// Unused dependencies are reported as warnings unless they are denied.
+#[derive(Clone)]
+pub struct Unused {
+    /// The identifiers of the dependencies to never report.
+    allow: Vec<String>,
+
+    /// Whether unused dependencies let the detection fail.
+    deny: bool,
+
+    /// Whether the unused dependencies shall be removed from the manifests.
+    fix: bool,
+
+    /// The information about the workspace to check.
+    metadata: std::sync::Arc<crate::Metadata>,
+
+    /// Whether the compiler shall be asked for the unused dependencies.
+    precise: bool,
+}
+
+/// The number of compiled units and lint reports within the precise mode.
+#[derive(Default)]
+struct Units {
+    /// The number of lint reports per package and dependency identifier.
+    reports: std::collections::HashMap<(String, String), usize>,
+
+    /// The number of compiled test units per package.
+    tests: std::collections::HashMap<String, usize>,
+
+    /// The number of compiled units per package.
+    total: std::collections::HashMap<String, usize>,
+}
+
+impl Unused {
+    /// Ask the compiler for the unused dependencies of each compiled unit.
+    ///
+    /// The workspace is checked in a dedicated target directory such that the
+    /// regular build artifacts are not invalidated by the changed flags.
+    fn compile(&self) -> Option<Units> {
+        let flags = std::env::var("RUSTFLAGS")
+            .ok()
+            .filter(|flags| !flags.trim().is_empty())
+            .into_iter()
+            .chain(std::iter::once("-W unused_crate_dependencies".into()))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let target = self
+            .metadata
+            .workspace()
+            .target()
+            .join("optimise")
+            .join("unused");
+        let mut process = crate::Process::new(
+            "cargo",
+            vec![
+                "check".into(),
+                "--workspace".into(),
+                "--all-targets".into(),
+                "--message-format=json".into(),
+                "--target-dir".into(),
+                target.to_string_lossy().into(),
+            ],
+            crate::Verbosity::Silent,
+        )
+        .env("RUSTFLAGS", &flags);
+
+        if process.run().is_failure() || !process.success() {
+            return None;
+        }
+
+        let mut units = Units::default();
+
+        for message in process
+            .stdout()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        {
+            let package = message["package_id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            let kinds = message["target"]["kind"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(serde_json::Value::as_str)
+                .collect::<Vec<&str>>();
+
+            match message["reason"].as_str() {
+                Some("compiler-artifact") if !kinds.contains(&"custom-build") => {
+                    if message["profile"]["test"].as_bool() == Some(true)
+                        || kinds.iter().any(|kind| ["bench", "example"].contains(kind))
+                    {
+                        *units.tests.entry(package.clone()).or_default() += 1;
+                    }
+
+                    *units.total.entry(package).or_default() += 1;
+                }
+                Some("compiler-message")
+                    if message["message"]["code"]["code"].as_str()
+                        == Some("unused_crate_dependencies") =>
+                {
+                    if let Some(identifier) = message["message"]["message"]
+                        .as_str()
+                        .and_then(|text| text.strip_prefix("extern crate `"))
+                        .and_then(|text| text.split('`').next())
+                    {
+                        *units
+                            .reports
+                            .entry((package, identifier.into()))
+                            .or_default() += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(units)
+    }
+
+    /// Whether the unused dependencies are removed from the manifests.
+    #[must_use]
+    pub const fn is_mutating(&self) -> bool {
+        self.fix
+    }
+
+    /// Configure a new detection of the unused dependencies.
+    #[must_use]
+    pub fn new(
+        config: &crate::UnusedConfig,
+        fix: bool,
+        metadata: std::sync::Arc<crate::Metadata>,
+    ) -> Self {
+        Self {
+            allow: config.allow().to_vec(),
+            deny: config.deny(),
+            fix,
+            metadata,
+            precise: config.precise(),
+        }
+    }
+
+    /// Remove the given dependency from the manifest of the given package.
+    ///
+    /// The dependency is removed from all tables of its kind, including the
+    /// platform specific ones.  The manifest is only written if it could be
+    /// understood and the dependency was found, see `strip`.
+    fn remove(package: &crate::Package, dependency: &crate::Dependency) -> bool {
+        std::fs::read_to_string(package.manifest_path())
+            .ok()
+            .and_then(|manifest| Self::strip(&manifest, dependency.kind(), dependency.key()))
+            .is_some_and(|manifest| std::fs::write(package.manifest_path(), manifest).is_ok())
+    }
+
+    /// Detect the unused dependencies of the workspace members.
+    ///
+    /// Optional dependencies are reported but never removed since they are
+    /// likely to be referred to by the features of the package.
+    #[must_use]
+    pub fn run(&self) -> crate::Findings {
+        let mut findings = crate::Findings::default();
+        let units = if self.precise {
+            let Some(units) = self.compile() else {
+                findings.error("failed to check the workspace for unused dependencies".into());
+                return findings;
+            };
+
+            Some(units)
+        } else {
+            None
+        };
+
+        for package in self.metadata.members() {
+            let sources = Self::sources(package);
+
+            for dependency in package.dependencies() {
+                let identifier = dependency.identifier();
+
+                if self.allow.contains(&identifier)
+                    || self.allow.iter().any(|name| name == dependency.name())
+                {
+                    continue;
+                }
+
+                let unused = match (&units, dependency.kind()) {
+                    (Some(units), None | Some("dev")) => {
+                        let available = if dependency.kind().is_some() {
+                            &units.tests
+                        } else {
+                            &units.total
+                        };
+                        let available = available.get(package.id()).copied().unwrap_or_default();
+                        let reports = units
+                            .reports
+                            .get(&(package.id().into(), identifier.clone()))
+                            .copied()
+                            .unwrap_or_default();
+
+                        available > 0 && reports == available
+                    }
+                    _ => !sources.iter().any(|source| Self::uses(source, &identifier)),
+                };
+
+                if !unused {
+                    continue;
+                }
+
+                let kind = dependency
+                    .kind()
+                    .map_or_else(String::new, |kind| format!(" ({kind})"));
+
+                if self.fix && !dependency.optional() && Self::remove(package, dependency) {
+                    findings.warning(format!(
+                        "{}:  removed unused dependency `{}`{kind}",
+                        package.name(),
+                        dependency.key()
+                    ));
+                } else {
+                    let finding = format!(
+                        "{}:  unused dependency `{}`{kind}",
+                        package.name(),
+                        dependency.key()
+                    );
+
+                    if self.deny {
+                        findings.error(finding);
+                    } else {
+                        findings.warning(finding);
+                    }
+                }
+            }
+        }
+
+        findings
+    }
+
+    /// Read the Rust sources of the given package.
+    ///
+    /// Nested packages are skipped since their sources belong to them.
+    fn sources(package: &crate::Package) -> Vec<String> {
+        let Some(root) = package.manifest_path().parent() else {
+            return Vec::new();
+        };
+        let nested = root.to_path_buf();
+
+        ignore::WalkBuilder::new(root)
+            .filter_entry(move |entry| {
+                entry.path() == nested
+                    || !entry.path().join("Cargo.toml").is_file() && entry.file_name() != "target"
+            })
+            .build()
+            .flatten()
+            .filter(|entry| {
+                entry.path().extension().and_then(std::ffi::OsStr::to_str) == Some("rs")
+            })
+            .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+            .collect()
+    }
+
+    /// Remove the dependency with the given kind and key from the given
+    /// manifest.
+    ///
+    /// The manifest is edited by `toml_edit` such that its formatting and its
+    /// comments are kept.  Since `toml_edit` writes plain line feeds, these
+    /// are turned into carriage returns and line feeds again if the manifest
+    /// used them.  If the dependency is not declared, nothing is returned.
+    fn strip(manifest: &str, kind: Option<&str>, key: &str) -> Option<String> {
+        let mut document = manifest.parse::<toml_edit::Document>().ok()?;
+        let table = match kind {
+            Some("build") => "build-dependencies",
+            Some("dev") => "dev-dependencies",
+            _ => "dependencies",
+        };
+        let mut removed = document
+            .get_mut(table)
+            .and_then(toml_edit::Item::as_table_like_mut)
+            .and_then(|dependencies| dependencies.remove(key))
+            .is_some();
+
+        if let Some(targets) = document
+            .get_mut("target")
+            .and_then(toml_edit::Item::as_table_like_mut)
+        {
+            for (_, target) in targets.iter_mut() {
+                removed |= target
+                    .get_mut(table)
+                    .and_then(toml_edit::Item::as_table_like_mut)
+                    .and_then(|dependencies| dependencies.remove(key))
+                    .is_some();
+            }
+        }
+
+        let stripped = document.to_string();
+
+        removed.then(|| {
+            if manifest.contains("\r\n") {
+                stripped.replace("\r\n", "\n").replace('\n', "\r\n")
+            } else {
+                stripped
+            }
+        })
+    }
+
+    /// Whether the given source refers to the dependency with the given
+    /// identifier.
+    ///
+    /// A dependency is considered used if its identifier is followed by a path
+    /// separator or preceded by `use` or `extern crate`.
+    fn uses(source: &str, identifier: &str) -> bool {
+        source.match_indices(identifier).any(|(index, _)| {
+            let before = &source[..index];
+            let after = &source[index + identifier.len()..];
+            let boundary = |character: Option<char>| {
+                !character.is_some_and(|character| character.is_alphanumeric() || character == '_')
+            };
+
+            boundary(before.chars().next_back())
+                && boundary(after.chars().next())
+                && (after.trim_start().starts_with("::")
+                    || before.trim_end().ends_with("use")
+                    || before.trim_end().ends_with("extern crate"))
+        })
+    }
+}
+
+impl std::fmt::Display for Unused {
+    /// Implements the `Display` trait.
+    ///
+    /// A detection will be formatted like a command line naming its mode as
+    /// well as the allowed dependencies.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unused")?;
+
+        if self.precise {
+            write!(f, " --precise")?;
+        }
+
+        if self.fix {
+            write!(f, " --fix")?;
+        }
+
+        if self.deny {
+            write!(f, " --deny")?;
+        }
+
+        for identifier in &self.allow {
+            write!(f, " --allow {identifier}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// A manifest declaring the dependency `foo` in several ways.
+    const MANIFEST: &str = r#"[package]
+name = "example"   # trailing comment
+
+[dependencies]
+bar = "1"
+foo = { version = "1", features = ["std"] }
+
+[target.'cfg(unix)'.dependencies]
+foo = "1"
+
+[dev-dependencies.foo]
+version = "1"
+"#;
+
+    #[test]
+    fn strip() {
+        assert_eq!(
+            super::Unused::strip(MANIFEST, None, "foo").unwrap(),
+            r#"[package]
+name = "example"   # trailing comment
+
+[dependencies]
+bar = "1"
+
+[target.'cfg(unix)'.dependencies]
+
+[dev-dependencies.foo]
+version = "1"
+"#
+        );
+    }
+
+    #[test]
+    fn strip_crlf() {
+        let manifest = MANIFEST.replace('\n', "\r\n");
+        let stripped = super::Unused::strip(&manifest, Some("dev"), "foo").unwrap();
+
+        assert!(!stripped.contains("[dev-dependencies.foo]"));
+        assert!(!stripped.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn strip_missing() {
+        assert!(super::Unused::strip(MANIFEST, Some("build"), "foo").is_none());
+        assert!(super::Unused::strip(MANIFEST, None, "baz").is_none());
+        assert!(super::Unused::strip("[dependencies", None, "foo").is_none());
+    }
+
+    #[test]
+    fn uses_path() {
+        assert!(super::Unused::uses("let x = foo::bar();", "foo"));
+        assert!(super::Unused::uses("foo ::bar", "foo"));
+        assert!(!super::Unused::uses("let foo = 1;", "foo"));
+        assert!(!super::Unused::uses("barfoo::baz", "foo"));
+        assert!(!super::Unused::uses("foo_bar::baz", "foo"));
+    }
+
+    #[test]
+    fn uses_use() {
+        assert!(super::Unused::uses("use foo;", "foo"));
+        assert!(super::Unused::uses("pub use foo as bar;", "foo"));
+        assert!(super::Unused::uses("extern crate foo;", "foo"));
+        assert!(!super::Unused::uses("fn refuse(foo: u8) {}", "foo"));
+    }
+}
+
+/******************************************************************************/
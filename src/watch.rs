@@ -0,0 +1,164 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The watch mode of this application.
+///
+/// In watch mode, the workspace is monitored for changes.  As soon as the
+/// sources of the project change, the non-mutating steps of the application
+/// are run again.
+pub struct Watch {
+    /// The time to wait for further changes before the steps will be run.
+    debounce: std::time::Duration,
+
+    /// The rules of the `.gitignore` files, the innermost directories first.
+    ignore: Vec<ignore::gitignore::Gitignore>,
+
+    /// The directory to monitor.
+    root: std::path::PathBuf,
+
+    /// The directory Cargo writes its build artifacts to.
+    target: std::path::PathBuf,
+}
+
+impl Watch {
+    /// Whether the given path is ignored by the `.gitignore` files.
+    ///
+    /// Like for Git, the rules of the innermost `.gitignore` matching the path
+    /// take precedence over those of the outer ones.
+    fn ignored(&self, path: &std::path::Path) -> bool {
+        self.ignore
+            .iter()
+            .filter(|ignore| path.starts_with(ignore.path()))
+            .map(|ignore| ignore.matched_path_or_any_parents(path, path.is_dir()))
+            .find(|matched| !matched.is_none())
+            .is_some_and(|matched| matched.is_ignore())
+    }
+
+    /// Configure the watch mode for the given workspace.
+    ///
+    /// The ignore rules are read from the `.gitignore` files of the workspace
+    /// when the watch mode is configured, each applying to the directory it
+    /// is located in.  Malformed files are ignored.  The target directory and
+    /// `.git/` are always ignored.
+    #[must_use]
+    pub fn new(workspace: &crate::Workspace, debounce: std::time::Duration) -> Self {
+        let root = workspace
+            .root()
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.root().to_path_buf());
+        let target = workspace
+            .target()
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.target().to_path_buf());
+        let mut ignore = crate::Workspace::new(&root, &target)
+            .files()
+            .into_iter()
+            .filter(|path| path.file_name() == Some(std::ffi::OsStr::new(".gitignore")))
+            .filter_map(|path| {
+                let (ignore, error) = ignore::gitignore::Gitignore::new(path);
+                error.is_none().then_some(ignore)
+            })
+            .collect::<Vec<ignore::gitignore::Gitignore>>();
+        ignore.sort_by_key(|ignore| std::cmp::Reverse(ignore.path().components().count()));
+
+        Self {
+            debounce,
+            ignore,
+            root,
+            target,
+        }
+    }
+
+    /// Whether the given event is a change to the sources of the project.
+    ///
+    /// Mere accesses and metadata changes are not considered to be changes.
+    /// The same applies to paths within the target directory, within `.git/`
+    /// and to those ignored by the `.gitignore` files.
+    fn relevant(&self, event: &notify::Event) -> bool {
+        match event.kind {
+            notify::EventKind::Access(_)
+            | notify::EventKind::Modify(notify::event::ModifyKind::Metadata(_)) => false,
+            _ => event.paths.iter().any(|path| {
+                path.starts_with(&self.root)
+                    && !path.starts_with(&self.target)
+                    && !path.starts_with(self.root.join(".git"))
+                    && !self.ignored(path)
+            }),
+        }
+    }
+
+    /// Run the given application whenever the sources of the project change.
+    ///
+    /// The non-mutating steps of the given application will be run once at the
+    /// beginning and then each time the sources change.  Mutating steps, such
+    /// as `cargo fmt`, are never run in watch mode since their own writes would
+    /// trigger the next run immediately.
+    ///
+    /// Bursts of changes, as caused by saving multiple files at once, are
+    /// collected until no further change occurs for the configured debounce
+    /// time.  Then, the screen will be cleared and the steps will be run again,
+    /// followed by a compact result line.
+    ///
+    /// This method only returns if the file system watcher fails.  The returned
+    /// `sysexits::ExitCode` describes the failure.
+    #[must_use]
+    pub fn run(&self, application: &crate::Application) -> sysexits::ExitCode {
+        let application = application.non_mutating();
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let Ok(mut watcher) = notify::recommended_watcher(sender) else {
+            eprintln!("Failed to set up the file system watcher!");
+            return sysexits::ExitCode::OsErr;
+        };
+
+        if notify::Watcher::watch(&mut watcher, &self.root, notify::RecursiveMode::Recursive)
+            .is_err()
+        {
+            eprintln!("Failed to watch '{}'!", self.root.display());
+            return sysexits::ExitCode::IoErr;
+        }
+
+        loop {
+            print!("\x1B[2J\x1B[1;1H");
+
+            match application.run() {
+                sysexits::ExitCode::Ok => println!("\nAll steps succeeded."),
+                code => println!("\nA step failed with {code}."),
+            }
+
+            println!("Watching '{}' for changes ...", self.root.display());
+
+            loop {
+                match receiver.recv() {
+                    Ok(Ok(event)) if self.relevant(&event) => break,
+                    Ok(_) => (),
+                    Err(_) => {
+                        eprintln!("The file system watcher stopped unexpectedly!");
+                        return sysexits::ExitCode::OsErr;
+                    }
+                }
+            }
+
+            while receiver.recv_timeout(self.debounce).is_ok() {}
+        }
+    }
+}
+
+/******************************************************************************/
@@ -0,0 +1,78 @@
+/*********************** GNU General Public License 2.0 ***********************\
+|                                                                              |
+|  Copyright (C) 2022 Kevin Matthes                                            |
+|                                                                              |
+|  This program is free software; you can redistribute it and/or modify        |
+|  it under the terms of the GNU General Public License as published by        |
+|  the Free Software Foundation; either version 2 of the License, or           |
+|  (at your option) any later version.                                         |
+|                                                                              |
+|  This program is distributed in the hope that it will be useful,             |
+|  but WITHOUT ANY WARRANTY; without even the implied warranty of              |
+|  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the               |
+|  GNU General Public License for more details.                                |
+|                                                                              |
+|  You should have received a copy of the GNU General Public License along     |
+|  with this program; if not, write to the Free Software Foundation, Inc.,     |
+|  51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.                 |
+|                                                                              |
+\******************************************************************************/
+
+/// The location of the Cargo workspace to optimise.
+#[derive(Clone)]
+pub struct Workspace {
+    /// The directory containing the workspace manifest.
+    root: std::path::PathBuf,
+
+    /// The directory Cargo writes its build artifacts to.
+    target: std::path::PathBuf,
+}
+
+impl Workspace {
+    /// List all files of the workspace.
+    ///
+    /// The rules of the `.gitignore` files are respected, even if the
+    /// workspace should not be a Git repository.  The target directory and
+    /// `.git/` are skipped, as well.  The files are returned in sorted order.
+    #[must_use]
+    pub fn files(&self) -> Vec<std::path::PathBuf> {
+        let mut files = ignore::WalkBuilder::new(&self.root)
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build()
+            .filter_map(Result::ok)
+            .map(ignore::DirEntry::into_path)
+            .filter(|path| path.is_file() && !path.starts_with(&self.target))
+            .collect::<Vec<std::path::PathBuf>>();
+
+        files.sort();
+        files
+    }
+
+    /// Create a new instance from the given directories.
+    ///
+    /// Usually, the directories are taken from the `Metadata` of the workspace
+    /// such that the same rules apply as for Cargo itself.
+    #[must_use]
+    pub fn new(root: &std::path::Path, target: &std::path::Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            target: target.to_path_buf(),
+        }
+    }
+
+    /// The directory containing the workspace manifest.
+    #[must_use]
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+
+    /// The directory Cargo writes its build artifacts to.
+    #[must_use]
+    pub fn target(&self) -> &std::path::Path {
+        &self.target
+    }
+}
+
+/******************************************************************************/